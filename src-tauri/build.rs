@@ -1,3 +1,6 @@
 fn main() {
-    tauri_build::build()
+    // 命令行工具不需要生成 Tauri 上下文
+    if std::env::var_os("CARGO_FEATURE_GUI").is_some() {
+        tauri_build::build()
+    }
 }
@@ -0,0 +1,703 @@
+//! 蓝图生成命令行工具，供脚本和构建流程使用
+
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand};
+use factorio_signal_blueprints_lib::{
+    core::{
+        decode_blueprint_string, diff_signal_dict, encode_png,
+        generate_all_item_constant_combinator_blueprint, generate_animation_blueprint,
+        generate_image_blueprint, generate_screen_blueprint, generate_tiled_display_blueprint,
+        inspect_blueprint, parse_signal_dict, reference_warnings, render_blueprint_png,
+        signal_dict_from_blueprint, signal_dict_from_data_dump, simulate_screen,
+        validate_blueprint_string, AllSignalsOptions, AlphaMode, AnimationOptions, BluePrint,
+        BluePrintError, BlueprintRecord, CountMode, DictSignal, DitherMode, EncodeOptions,
+        FlipAxis, GameVersion, ImageMode, ImageOptions, RenderOptions, ResizeMode, Result,
+        Rotation, SignalProfile, Transform,
+    },
+    AppData,
+};
+
+#[derive(Parser)]
+#[command(name = "fbp-cli", version, about = "异星工厂信号蓝图生成工具")]
+struct Cli {
+    /// 信号字典文件，不指定时使用内置字典
+    #[arg(long, global = true)]
+    signal_dict: Option<PathBuf>,
+    /// 信号配置：vanilla（无品质）、space-age 或 modded
+    #[arg(long, global = true, default_value = "space-age", value_parser = parse_profile)]
+    profile: SignalProfile,
+    /// 不作为像素信号的信号名称，用逗号分隔，可以使用 `*` 通配，不指定时使用默认列表
+    #[arg(long, global = true)]
+    exclude: Option<String>,
+    /// 参考蓝图字符串文件，生成的蓝图使用了其中常量运算器没有的信号时给出警告
+    #[arg(long, global = true)]
+    reference: Option<PathBuf>,
+    /// 生成的蓝图记录的游戏版本，导入主版本号不同的蓝图时给出警告
+    #[arg(
+        long,
+        global = true,
+        default_value_t = GameVersion::TARGET,
+        value_parser = parse_game_version
+    )]
+    game_version: GameVersion,
+    /// zlib 压缩等级 0~9，9 为最佳压缩
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 6,
+        value_parser = clap::value_parser!(u32).range(0..=9)
+    )]
+    compression_level: u32,
+    /// 省略物品类型、普通品质等默认值，缩短蓝图字符串
+    #[arg(long, global = true)]
+    minify: bool,
+    /// 将 JSON、压缩后和最终字符串的大小输出到标准错误
+    #[arg(long, global = true)]
+    size_report: bool,
+    /// 输出文件，不指定时输出到标准输出
+    #[arg(short, long, global = true)]
+    output: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// 图片转换参数
+#[derive(Args)]
+struct ImageArgs {
+    /// 图片模式：rgb 按信号打包颜色，需配合显示屏；palette 量化为电灯颜色，生成自带电灯的显示屏
+    #[arg(long, default_value = "rgb")]
+    mode: ImageMode,
+    /// 抖动算法：none、floyd-steinberg、atkinson、bayer
+    #[arg(long, default_value = "none")]
+    dither: DitherMode,
+    /// 每个颜色通道保留的色阶数（2~255），不指定时不减色
+    #[arg(long)]
+    palette_levels: Option<u8>,
+    /// 缩放方式：stretch 拉伸、fit 完整显示并填充背景、fill 铺满并裁剪
+    #[arg(long, default_value = "stretch")]
+    resize: ResizeMode,
+    /// fit 模式的背景色，如 #000000
+    #[arg(long, default_value = "#000000", value_parser = parse_color)]
+    background: [u8; 3],
+    /// fill 模式裁剪保留的焦点，如 0.5,0.3，不指定时居中
+    #[arg(long, value_parser = parse_focal_point)]
+    focal_point: Option<[f32; 2]>,
+    /// 按图片宽高比由宽度计算高度
+    #[arg(long)]
+    auto_height: bool,
+    /// 透明像素处理：ignore 忽略、composite 与背景色混合、off 对应电灯熄灭
+    #[arg(long, default_value = "ignore")]
+    alpha: AlphaMode,
+}
+
+/// 解析内置信号配置的名称
+fn parse_profile(value: &str) -> std::result::Result<SignalProfile, String> {
+    value.parse().map_err(|e: BluePrintError| e.to_string())
+}
+
+/// 解析 2.0.28 格式的游戏版本
+fn parse_game_version(value: &str) -> std::result::Result<GameVersion, String> {
+    value.parse().map_err(|e: BluePrintError| e.to_string())
+}
+
+/// 解析 #RRGGBB 格式的颜色
+fn parse_color(value: &str) -> std::result::Result<[u8; 3], String> {
+    let hex = value.trim_start_matches('#');
+    let color = u32::from_str_radix(hex, 16).map_err(|e| e.to_string())?;
+    if hex.len() != 6 {
+        return Err(format!("颜色格式应为 #RRGGBB：{}", value));
+    }
+    Ok([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+}
+
+/// 解析 x,y 格式的焦点
+fn parse_focal_point(value: &str) -> std::result::Result<[f32; 2], String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("焦点格式应为 x,y：{}", value))?;
+    let parse = |v: &str| v.trim().parse::<f32>().map_err(|e| e.to_string());
+    Ok([parse(x)?, parse(y)?])
+}
+
+impl ImageArgs {
+    fn options(&self) -> ImageOptions {
+        ImageOptions {
+            mode: self.mode,
+            dither: self.dither,
+            palette_levels: self.palette_levels,
+            resize: self.resize,
+            background: self.background,
+            focal_point: self.focal_point,
+            auto_height: self.auto_height,
+            alpha: self.alpha,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 生成彩色显示屏蓝图
+    Screen {
+        #[arg(long)]
+        width: u32,
+        #[arg(long)]
+        height: u32,
+        /// 用红线连接电灯
+        #[arg(long)]
+        red_line: bool,
+        /// 用绿线连接电灯
+        #[arg(long)]
+        green_line: bool,
+        /// 电灯保持打开
+        #[arg(long)]
+        keep_open: bool,
+        /// 电灯模式：rgb 按像素信号显示颜色；palette 像素信号不为0时点亮，颜色取网络中的颜色信号
+        #[arg(long, default_value = "rgb")]
+        mode: ImageMode,
+        /// 信号为0时电灯熄灭，配合 --alpha off 生成的图片使用
+        #[arg(long)]
+        transparent_off: bool,
+        /// 顺时针旋转角度：90、180 或 270
+        #[arg(long)]
+        rotate: Option<Rotation>,
+    },
+    /// 生成包含图片信息的常量运算器蓝图
+    Image {
+        /// 图片文件
+        input: PathBuf,
+        #[arg(long)]
+        width: u32,
+        /// 使用 --auto-height 时可省略
+        #[arg(long, required_unless_present = "auto_height")]
+        height: Option<u32>,
+        /// 调色板模式的电灯保持打开
+        #[arg(long)]
+        keep_open: bool,
+        #[command(flatten)]
+        image_args: ImageArgs,
+    },
+    /// 生成可超过像素上限的分块显示屏，每块面板附带图片数据
+    Display {
+        /// 图片文件
+        input: PathBuf,
+        #[arg(long)]
+        width: u32,
+        /// 使用 --auto-height 时可省略
+        #[arg(long, required_unless_present = "auto_height")]
+        height: Option<u32>,
+        /// 用红线连接电灯，未指定信号线时默认使用红线
+        #[arg(long)]
+        red_line: bool,
+        /// 用绿线连接电灯
+        #[arg(long)]
+        green_line: bool,
+        /// 电灯保持打开
+        #[arg(long)]
+        keep_open: bool,
+        #[command(flatten)]
+        image_args: ImageArgs,
+    },
+    /// 由GIF或帧图片文件夹生成动画显示屏
+    Animation {
+        /// GIF文件或按文件名排序的帧图片文件夹
+        input: PathBuf,
+        #[arg(long)]
+        width: u32,
+        /// 使用 --auto-height 时可省略
+        #[arg(long, required_unless_present = "auto_height")]
+        height: Option<u32>,
+        /// 每帧持续的游戏刻数，不指定时使用GIF自带的帧延迟
+        #[arg(long)]
+        frame_delay: Option<u32>,
+        /// 只播放一次并停在最后一帧
+        #[arg(long)]
+        once: bool,
+        /// 用红线连接电灯，未指定信号线时默认使用红线
+        #[arg(long)]
+        red_line: bool,
+        /// 用绿线连接电灯
+        #[arg(long)]
+        green_line: bool,
+        /// 电灯保持打开
+        #[arg(long)]
+        keep_open: bool,
+        #[command(flatten)]
+        image_args: ImageArgs,
+    },
+    /// 将蓝图字符串解码为JSON
+    Decode {
+        /// 蓝图字符串文件，不指定或为 `-` 时从标准输入读取
+        input: Option<PathBuf>,
+    },
+    /// 解码蓝图、蓝图书或规划工具并输出概要：实体数量、范围、导线、信号数量和游戏版本
+    Inspect {
+        /// 蓝图字符串文件，不指定或为 `-` 时从标准输入读取
+        input: Option<PathBuf>,
+    },
+    /// 严格校验蓝图字符串，失败时给出出错的阶段和位置
+    Validate {
+        /// 蓝图字符串文件，不指定或为 `-` 时从标准输入读取
+        input: Option<PathBuf>,
+    },
+    /// 变换蓝图，依次执行镜像、旋转和平移，实体方向随之改变
+    Transform {
+        /// 蓝图字符串文件，不指定或为 `-` 时从标准输入读取
+        input: Option<PathBuf>,
+        /// 镜像方向：horizontal 或 vertical
+        #[arg(long)]
+        flip: Option<FlipAxis>,
+        /// 顺时针旋转角度：90、180 或 270
+        #[arg(long)]
+        rotate: Option<Rotation>,
+        /// 平移的格数，格式为 dx,dy
+        #[arg(
+            long,
+            value_delimiter = ',',
+            num_args = 1,
+            allow_hyphen_values = true,
+            value_name = "DX,DY"
+        )]
+        translate: Vec<i32>,
+    },
+    /// 将JSON编码为蓝图字符串
+    Encode {
+        /// JSON文件，不指定或为 `-` 时从标准输入读取
+        input: Option<PathBuf>,
+    },
+    /// 生成包含全部信号的常量运算器蓝图
+    AllSignals {
+        /// 包含的品质，用逗号分隔
+        #[arg(long, default_value = "normal", value_delimiter = ',')]
+        qualities: Vec<String>,
+        /// 包含的信号类型，用逗号分隔，物品信号为 item，不指定时包含全部类型
+        #[arg(long, value_delimiter = ',')]
+        types: Vec<String>,
+        /// 每个分组的信号数量
+        #[arg(long, default_value_t = 1000)]
+        section_size: usize,
+        /// 数值模式：fixed 或 sequential（依次为 1..N）
+        #[arg(long, default_value = "fixed")]
+        count_mode: CountMode,
+        /// 固定数值模式下每个信号的数值
+        #[arg(long, default_value_t = 1)]
+        count: i32,
+    },
+    /// 由 `factorio --dump-data` 导出的 data-raw-dump.json 生成信号字典
+    ImportDump {
+        /// data-raw-dump.json 文件
+        input: PathBuf,
+    },
+    /// 由常量运算器蓝图生成信号字典
+    DictFromBlueprint {
+        /// 蓝图字符串文件，不指定或为 `-` 时从标准输入读取
+        input: Option<PathBuf>,
+    },
+    /// 比较当前信号字典与参考蓝图，输出新增、删除和类型变化的信号
+    DiffDict {
+        /// 包含目标游戏版本全部信号的常量运算器蓝图字符串文件
+        reference: PathBuf,
+    },
+    /// 绘制蓝图的PNG预览图
+    Render {
+        /// 蓝图字符串文件，不指定或为 `-` 时从标准输入读取
+        input: Option<PathBuf>,
+        /// 与显示屏配套的图片蓝图字符串文件，其信号叠加到每个电灯上
+        #[arg(long)]
+        paired: Option<PathBuf>,
+        /// 每格的像素数
+        #[arg(long, default_value_t = 8)]
+        scale: u32,
+        /// 不绘制信号线
+        #[arg(long)]
+        no_wires: bool,
+    },
+    /// 模拟图片蓝图接到显示屏后的画面，输出不匹配问题的JSON报告
+    Simulate {
+        /// 显示屏蓝图字符串文件
+        screen: PathBuf,
+        /// 图片蓝图字符串文件
+        image: PathBuf,
+        /// 保存还原画面的PNG文件
+        #[arg(long)]
+        preview: Option<PathBuf>,
+    },
+}
+
+/// 命令的输出内容
+enum Output {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl From<String> for Output {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+/// 读取文件内容，路径为空或 `-` 时读取标准输入
+fn read_input(input: Option<&PathBuf>) -> Result<String> {
+    match input {
+        Some(path) if path.as_os_str() != "-" => Ok(fs::read_to_string(path)?),
+        _ => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+            Ok(content)
+        }
+    }
+}
+
+fn load_data(cli: &Cli) -> Result<AppData> {
+    let mut data = match &cli.signal_dict {
+        Some(path) => AppData::from_path(path)?,
+        None => AppData::embedded()?,
+    };
+    data.set_profile(cli.profile.clone())?;
+    if let Some(exclude) = &cli.exclude {
+        data.set_excluded_signals(exclude.split(',').map(|v| v.to_string()).collect())?;
+    }
+    if let Some(path) = &cli.reference {
+        data.reference_dict = Some(load_reference(path)?);
+    }
+    data.game_version = cli.game_version;
+    data.encode_options = encode_options(cli);
+    Ok(data)
+}
+
+fn encode_options(cli: &Cli) -> EncodeOptions {
+    EncodeOptions {
+        compression_level: cli.compression_level,
+        minify: cli.minify,
+    }
+}
+
+/// 按命令行参数编码蓝图字符串
+fn encoded(record: impl Into<BlueprintRecord>, cli: &Cli) -> Result<Output> {
+    let (blueprint_string, size) = record.into().encode(&encode_options(cli))?;
+    if cli.size_report {
+        eprintln!(
+            "JSON {} 字节，压缩后 {} 字节，蓝图字符串 {} 字符",
+            size.json_bytes, size.compressed_bytes, size.string_length
+        );
+    }
+    Ok(blueprint_string.into())
+}
+
+/// 由参考蓝图字符串文件生成参考字典
+fn load_reference(path: &Path) -> Result<Vec<DictSignal>> {
+    let dict = decode_blueprint_string(&fs::read_to_string(path)?)?;
+    parse_signal_dict(&signal_dict_from_blueprint(&dict)?)
+}
+
+/// 与内置字典相同，每个信号占一行
+fn format_signal_dict(dict: &serde_json::Value) -> String {
+    let lines: Vec<String> = (0..dict.as_object().map_or(0, |m| m.len()))
+        .map(|i| format!("    \"{}\": {}", i, dict[i.to_string()]))
+        .collect();
+    format!("{{\n{}\n}}", lines.join(",\n"))
+}
+
+/// 导入的蓝图来自不兼容的游戏版本时输出警告
+fn warn_version(record: &BlueprintRecord, cli: &Cli) {
+    for warning in record.version_warnings(cli.game_version) {
+        eprintln!("警告：{}", warning);
+    }
+}
+
+/// 检查导入的蓝图字典的游戏版本，无法解析为蓝图对象时不检查
+fn warn_imported(dicts: &[&serde_json::Value], cli: &Cli) {
+    for dict in dicts {
+        if let Ok(record) = BlueprintRecord::new(dict) {
+            warn_version(&record, cli);
+        }
+    }
+}
+
+/// 输出生成的蓝图，按参考字典检查的警告输出到标准错误
+fn generated(blueprint: BluePrint, data: &AppData, cli: &Cli) -> Result<Output> {
+    for warning in reference_warnings(&blueprint, data) {
+        eprintln!("警告：{}", warning);
+    }
+    encoded(blueprint, cli)
+}
+
+fn run(cli: &Cli) -> Result<Output> {
+    match &cli.command {
+        Command::Screen {
+            width,
+            height,
+            red_line,
+            green_line,
+            keep_open,
+            mode,
+            transparent_off,
+            rotate,
+        } => {
+            let mut wire_type_list = Vec::new();
+            if *red_line {
+                wire_type_list.push(1);
+            }
+            if *green_line {
+                wire_type_list.push(2);
+            }
+            let data = load_data(cli)?;
+            let mut blueprint = generate_screen_blueprint(
+                *width,
+                *height,
+                Some(wire_type_list),
+                *keep_open,
+                *mode,
+                *transparent_off,
+                &data,
+            )?;
+            if let Some(rotation) = rotate {
+                blueprint.rotate(*rotation);
+            }
+            generated(blueprint, &data, cli)
+        }
+        Command::Image {
+            input,
+            width,
+            height,
+            keep_open,
+            image_args,
+        } => {
+            let data = load_data(cli)?;
+            let blueprint = generate_image_blueprint(
+                input,
+                *width,
+                height.unwrap_or_default(),
+                *keep_open,
+                &image_args.options(),
+                &data,
+            )?;
+            generated(blueprint, &data, cli)
+        }
+        Command::Display {
+            input,
+            width,
+            height,
+            red_line,
+            green_line,
+            keep_open,
+            image_args,
+        } => {
+            let mut wire_type_list = Vec::new();
+            if *red_line {
+                wire_type_list.push(1);
+            }
+            if *green_line {
+                wire_type_list.push(2);
+            }
+            let data = load_data(cli)?;
+            let blueprint = generate_tiled_display_blueprint(
+                input,
+                *width,
+                height.unwrap_or_default(),
+                Some(wire_type_list),
+                *keep_open,
+                &image_args.options(),
+                &data,
+            )?;
+            generated(blueprint, &data, cli)
+        }
+        Command::Animation {
+            input,
+            width,
+            height,
+            frame_delay,
+            once,
+            red_line,
+            green_line,
+            keep_open,
+            image_args,
+        } => {
+            let mut wire_type_list = Vec::new();
+            if *red_line {
+                wire_type_list.push(1);
+            }
+            if *green_line {
+                wire_type_list.push(2);
+            }
+            let animation = AnimationOptions {
+                frame_delay: *frame_delay,
+                loop_animation: !*once,
+            };
+            let data = load_data(cli)?;
+            let blueprint = generate_animation_blueprint(
+                input,
+                *width,
+                height.unwrap_or_default(),
+                Some(wire_type_list),
+                *keep_open,
+                &animation,
+                &image_args.options(),
+                &data,
+            )?;
+            generated(blueprint, &data, cli)
+        }
+        Command::Decode { input } => {
+            let content = read_input(input.as_ref())?;
+            let dict = decode_blueprint_string(&content)?;
+            let json = serde_json::to_string_pretty(&dict)
+                .map_err(|e| BluePrintError::Codec(e.to_string()))?;
+            Ok(json.into())
+        }
+        Command::Inspect { input } => {
+            let inspection = inspect_blueprint(&read_input(input.as_ref())?, cli.game_version)?;
+            for warning in &inspection.warnings {
+                eprintln!("警告：{}", warning);
+            }
+            let json = serde_json::to_string_pretty(&inspection.summary)
+                .map_err(|e| BluePrintError::Codec(e.to_string()))?;
+            Ok(json.into())
+        }
+        Command::Validate { input } => {
+            let record = validate_blueprint_string(&read_input(input.as_ref())?)?;
+            Ok(format!(
+                "蓝图有效：{}，{}个实体",
+                record.kind(),
+                record.entity_count()
+            )
+            .into())
+        }
+        Command::Transform {
+            input,
+            flip,
+            rotate,
+            translate,
+        } => {
+            let mut transforms = Vec::new();
+            if let Some(axis) = flip {
+                transforms.push(Transform::Flip { axis: *axis });
+            }
+            if let Some(rotation) = rotate {
+                transforms.push(Transform::Rotate {
+                    rotation: *rotation,
+                });
+            }
+            match translate.as_slice() {
+                [] => {}
+                &[dx, dy] => transforms.push(Transform::Translate { dx, dy }),
+                _ => {
+                    return Err(BluePrintError::InvalidInput(
+                        "平移格式应为 dx,dy".to_string(),
+                    ))
+                }
+            }
+            let record = BlueprintRecord::from_blueprint_string(&read_input(input.as_ref())?)?;
+            warn_version(&record, cli);
+            let BlueprintRecord::Blueprint(mut blueprint) = record else {
+                return Err(BluePrintError::InvalidInput(
+                    "只能变换单个蓝图！".to_string(),
+                ));
+            };
+            blueprint.transform(&transforms);
+            encoded(blueprint, cli)
+        }
+        Command::Encode { input } => {
+            let content = read_input(input.as_ref())?;
+            let dict =
+                serde_json::from_str(&content).map_err(|e| BluePrintError::Codec(e.to_string()))?;
+            encoded(BlueprintRecord::new(&dict)?, cli)
+        }
+        Command::AllSignals {
+            qualities,
+            types,
+            section_size,
+            count_mode,
+            count,
+        } => {
+            let options = AllSignalsOptions {
+                qualities: qualities.clone(),
+                signal_types: types.clone(),
+                section_size: *section_size,
+                count_mode: *count_mode,
+                count: *count,
+            };
+            let data = load_data(cli)?;
+            let blueprint = generate_all_item_constant_combinator_blueprint(&options, &data)?;
+            encoded(blueprint, cli)
+        }
+        Command::ImportDump { input } => {
+            let dump = serde_json::from_str(&fs::read_to_string(input)?)
+                .map_err(|e| BluePrintError::SignalDictionary(e.to_string()))?;
+            let dict = signal_dict_from_data_dump(&dump)?;
+            Ok(format_signal_dict(&dict).into())
+        }
+        Command::DictFromBlueprint { input } => {
+            let dict = decode_blueprint_string(&read_input(input.as_ref())?)?;
+            Ok(format_signal_dict(&signal_dict_from_blueprint(&dict)?).into())
+        }
+        Command::DiffDict { reference } => {
+            let data = load_data(cli)?;
+            let diff = diff_signal_dict(&data.signals, &load_reference(reference)?);
+            let json = serde_json::to_string_pretty(&diff)
+                .map_err(|e| BluePrintError::Codec(e.to_string()))?;
+            Ok(json.into())
+        }
+        Command::Render {
+            input,
+            paired,
+            scale,
+            no_wires,
+        } => {
+            let dict = decode_blueprint_string(&read_input(input.as_ref())?)?;
+            let paired = match paired {
+                Some(path) => Some(decode_blueprint_string(&fs::read_to_string(path)?)?),
+                None => None,
+            };
+            let dicts: Vec<_> = std::iter::once(&dict).chain(paired.as_ref()).collect();
+            warn_imported(&dicts, cli);
+            let options = RenderOptions {
+                scale: *scale,
+                show_wires: !*no_wires,
+            };
+            Ok(Output::Binary(render_blueprint_png(
+                &dict,
+                paired.as_ref(),
+                &options,
+            )?))
+        }
+        Command::Simulate {
+            screen,
+            image,
+            preview,
+        } => {
+            let screen = decode_blueprint_string(&fs::read_to_string(screen)?)?;
+            let image = decode_blueprint_string(&fs::read_to_string(image)?)?;
+            warn_imported(&[&screen, &image], cli);
+            let report = simulate_screen(&screen, &image)?;
+            if let Some(path) = preview {
+                fs::write(path, encode_png(&report.image)?)?;
+            }
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| BluePrintError::Codec(e.to_string()))?;
+            Ok(json.into())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = run(&cli).and_then(|output| match (&cli.output, output) {
+        (Some(path), Output::Text(content)) => Ok(fs::write(path, content)?),
+        (Some(path), Output::Binary(content)) => Ok(fs::write(path, content)?),
+        (None, Output::Text(content)) => Ok(writeln!(io::stdout().lock(), "{}", content)?),
+        (None, Output::Binary(content)) => Ok(io::stdout().lock().write_all(&content)?),
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,10 +1,42 @@
+use std::sync::{RwLock, RwLockReadGuard};
+
+use base64::{engine::general_purpose, Engine};
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use serde_json::Value;
+use tauri::{AppHandle, State};
 
 use crate::{
-    core::{generate_image_blueprint, generate_screen_blueprint}, AppData
+    bundled_app_data,
+    core::{
+        decode_blueprint_string, diff_signal_dict, encode_png, extract_blueprint_book,
+        generate_all_item_constant_combinator_blueprint, generate_animation_blueprint,
+        generate_blueprint_book, generate_display_deconstruction_planner, generate_image_blueprint,
+        generate_lamp_upgrade_planner, generate_screen_blueprint, generate_tiled_display_blueprint,
+        list_blueprint_book, parse_signal_dict, reference_warnings, render_blueprint_png,
+        signal_dict_from_blueprint, simulate_screen, AllSignalsOptions, AnimationOptions,
+        BluePrint, BluePrintError, BlueprintInspection, BlueprintRecord, BlueprintResponse,
+        BookChild, EncodeOptions, GameVersion, ImageMode, ImageOptions, RenderOptions, Rotation,
+        SignalDictDiff, SignalProfile, SimulationReport, Transform, DEFAULT_EXCLUDED_SIGNALS,
+    },
+    AppData,
 };
 
+/// 读取当前使用的信号字典，锁中毒时仍使用其中的数据
+fn app_data<'a>(state: &'a State<'_, RwLock<AppData>>) -> RwLockReadGuard<'a, AppData> {
+    state.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 返回生成的蓝图，并按参考字典检查其中的信号
+fn checked_response(
+    blueprint: BluePrint,
+    data: &AppData,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let warnings = reference_warnings(&blueprint, data);
+    let mut response = BlueprintResponse::new(blueprint, &data.encode_options)?;
+    response.warnings = warnings;
+    Ok(response)
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ScreenForm {
     width: u32,
@@ -15,11 +47,21 @@ pub struct ScreenForm {
     green_line: bool,
     #[serde(rename = "keepOpen")]
     keep_open: bool,
+    #[serde(default)]
+    mode: ImageMode,
+    #[serde(default, rename = "transparentOff")]
+    transparent_off: bool,
+    /// 顺时针旋转显示屏，以便贴合墙面
+    #[serde(default)]
+    rotation: Option<Rotation>,
 }
 
 /// 生成显示屏蓝图
 #[tauri::command]
-pub fn generate_screen_bp(form: ScreenForm, state: State<'_, AppData>) -> String {
+pub fn generate_screen_bp(
+    form: ScreenForm,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
     let mut wire_type_list = Vec::new();
     if form.red_line {
         wire_type_list.push(1);
@@ -28,17 +70,20 @@ pub fn generate_screen_bp(form: ScreenForm, state: State<'_, AppData>) -> String
         wire_type_list.push(2);
     }
 
-    let result = generate_screen_blueprint(
+    let data = app_data(&state);
+    let mut blueprint = generate_screen_blueprint(
         form.width,
         form.height,
         Some(wire_type_list),
         form.keep_open,
-        state.inner(),
-    );
-    match result {
-        Ok(res) => res,
-        Err(e) => e.to_string(),
+        form.mode,
+        form.transparent_off,
+        &data,
+    )?;
+    if let Some(rotation) = form.rotation {
+        blueprint.rotate(rotation);
     }
+    checked_response(blueprint, &data)
 }
 
 #[derive(Serialize, Deserialize)]
@@ -47,19 +92,449 @@ pub struct ImageForm {
     height: u32,
     #[serde(rename = "originalPath")]
     original_path: String,
+    #[serde(rename = "keepOpen")]
+    keep_open: bool,
+    #[serde(flatten)]
+    options: ImageOptions,
 }
 
 /// 生成图片蓝图
 #[tauri::command]
-pub fn generate_image_bp(form: ImageForm, state: State<'_, AppData>) -> String {
-    let result = generate_image_blueprint(
-        &form.original_path, 
-        form.width, 
-        form.height, 
-        state.inner()
-    );
-    match result {
-        Ok(res) => res,
-        Err(e) => e.to_string(),
+pub fn generate_image_bp(
+    form: ImageForm,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let data = app_data(&state);
+    let blueprint = generate_image_blueprint(
+        &form.original_path,
+        form.width,
+        form.height,
+        form.keep_open,
+        &form.options,
+        &data,
+    )?;
+    checked_response(blueprint, &data)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DisplayForm {
+    width: u32,
+    height: u32,
+    #[serde(rename = "originalPath")]
+    original_path: String,
+    #[serde(rename = "redLine")]
+    red_line: bool,
+    #[serde(rename = "greenLine")]
+    green_line: bool,
+    #[serde(rename = "keepOpen")]
+    keep_open: bool,
+    #[serde(flatten)]
+    options: ImageOptions,
+}
+
+/// 生成带图片数据的分块大显示屏蓝图
+#[tauri::command]
+pub fn generate_tiled_display_bp(
+    form: DisplayForm,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let mut wire_type_list = Vec::new();
+    if form.red_line {
+        wire_type_list.push(1);
     }
+    if form.green_line {
+        wire_type_list.push(2);
+    }
+
+    let data = app_data(&state);
+    let blueprint = generate_tiled_display_blueprint(
+        &form.original_path,
+        form.width,
+        form.height,
+        Some(wire_type_list),
+        form.keep_open,
+        &form.options,
+        &data,
+    )?;
+    checked_response(blueprint, &data)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AnimationForm {
+    width: u32,
+    height: u32,
+    #[serde(rename = "originalPath")]
+    original_path: String,
+    #[serde(rename = "redLine")]
+    red_line: bool,
+    #[serde(rename = "greenLine")]
+    green_line: bool,
+    #[serde(rename = "keepOpen")]
+    keep_open: bool,
+    #[serde(flatten)]
+    animation: AnimationOptions,
+    #[serde(flatten)]
+    options: ImageOptions,
+}
+
+/// 由GIF或帧图片文件夹生成动画显示屏蓝图
+#[tauri::command]
+pub fn generate_animation_bp(
+    form: AnimationForm,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let mut wire_type_list = Vec::new();
+    if form.red_line {
+        wire_type_list.push(1);
+    }
+    if form.green_line {
+        wire_type_list.push(2);
+    }
+
+    let data = app_data(&state);
+    let blueprint = generate_animation_blueprint(
+        &form.original_path,
+        form.width,
+        form.height,
+        Some(wire_type_list),
+        form.keep_open,
+        &form.animation,
+        &form.options,
+        &data,
+    )?;
+    checked_response(blueprint, &data)
+}
+
+/// 生成包含全部信号的常量运算器蓝图，用于测试解码和枚举信号
+#[tauri::command]
+pub fn generate_all_signals_bp(
+    options: AllSignalsOptions,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let data = app_data(&state);
+    let blueprint = generate_all_item_constant_combinator_blueprint(&options, &data)?;
+    BlueprintResponse::new(blueprint, &data.encode_options)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BookForm {
+    label: Option<String>,
+    blueprints: Vec<String>,
+}
+
+/// 将多个蓝图打包为蓝图书
+#[tauri::command]
+pub fn generate_book_bp(
+    form: BookForm,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let data = app_data(&state);
+    let book = generate_blueprint_book(form.label, &form.blueprints, &data)?;
+    let warnings = book
+        .blueprints
+        .iter()
+        .flat_map(|entry| entry.record.version_warnings(data.game_version))
+        .collect();
+    let mut response = BlueprintResponse::new(book, &data.encode_options)?;
+    response.warnings = warnings;
+    Ok(response)
+}
+
+/// 列出蓝图书中的蓝图，path为逐层进入子蓝图书的序号
+#[tauri::command]
+pub fn list_book_bp(blueprint: String, path: Vec<u32>) -> Result<Vec<BookChild>, BluePrintError> {
+    list_blueprint_book(&blueprint, &path)
+}
+
+/// 从蓝图书中取出单个蓝图
+#[tauri::command]
+pub fn extract_book_bp(
+    blueprint: String,
+    path: Vec<u32>,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let data = app_data(&state);
+    let record = extract_blueprint_book(&blueprint, &path)?;
+    let warnings = record.version_warnings(data.game_version);
+    let mut response = BlueprintResponse::new(record, &data.encode_options)?;
+    response.warnings = warnings;
+    Ok(response)
+}
+
+/// 对蓝图依次执行平移、旋转和镜像
+#[tauri::command]
+pub fn transform_bp(
+    blueprint: String,
+    transforms: Vec<Transform>,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let record = BlueprintRecord::from_blueprint_string(&blueprint)?;
+    let data = app_data(&state);
+    let warnings = record.version_warnings(data.game_version);
+    let BlueprintRecord::Blueprint(mut blueprint) = record else {
+        return Err(BluePrintError::InvalidInput(
+            "只能变换单个蓝图！".to_string(),
+        ));
+    };
+    blueprint.transform(&transforms);
+    let mut response = BlueprintResponse::new(blueprint, &data.encode_options)?;
+    response.warnings = warnings;
+    Ok(response)
+}
+
+/// 生成显示屏电灯的品质升级计划
+#[tauri::command]
+pub fn generate_upgrade_planner_bp(
+    quality: String,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let data = app_data(&state);
+    let planner = generate_lamp_upgrade_planner(&quality, &data)?;
+    BlueprintResponse::new(planner, &data.encode_options)
+}
+
+/// 生成只拆除电灯和运算器的拆除计划
+#[tauri::command]
+pub fn generate_deconstruction_planner_bp(
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintResponse, BluePrintError> {
+    let data = app_data(&state);
+    let planner = generate_display_deconstruction_planner(&data)?;
+    BlueprintResponse::new(planner, &data.encode_options)
+}
+
+/// 导入的蓝图来自不兼容的游戏版本时给出警告，无法解析为蓝图对象时不检查
+fn import_warnings(dicts: &[&Value], data: &AppData) -> Vec<String> {
+    dicts
+        .iter()
+        .filter_map(|dict| BlueprintRecord::new(dict).ok())
+        .flat_map(|record| record.version_warnings(data.game_version))
+        .collect()
+}
+
+#[derive(Serialize)]
+pub struct RenderResponse {
+    /// base64编码的PNG
+    png: String,
+    warnings: Vec<String>,
+}
+
+/// 绘制蓝图预览图
+///
+/// paired 为与显示屏配套的图片蓝图，其中的信号会叠加到每个电灯上
+#[tauri::command]
+pub fn render_bp(
+    blueprint: String,
+    paired: Option<String>,
+    options: Option<RenderOptions>,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<RenderResponse, BluePrintError> {
+    let dict = decode_blueprint_string(&blueprint)?;
+    let paired = match paired {
+        Some(paired) if !paired.trim().is_empty() => Some(decode_blueprint_string(&paired)?),
+        _ => None,
+    };
+    let png = render_blueprint_png(&dict, paired.as_ref(), &options.unwrap_or_default())?;
+    let dicts: Vec<&Value> = std::iter::once(&dict).chain(paired.as_ref()).collect();
+    Ok(RenderResponse {
+        png: general_purpose::STANDARD.encode(png),
+        warnings: import_warnings(&dicts, &app_data(&state)),
+    })
+}
+
+#[derive(Serialize)]
+pub struct SimulationResponse {
+    #[serde(flatten)]
+    report: SimulationReport,
+    /// base64编码的还原画面PNG
+    preview: String,
+    warnings: Vec<String>,
+}
+
+/// 模拟图片蓝图接到显示屏后的画面，并报告信号不匹配的问题
+#[tauri::command]
+pub fn simulate_bp(
+    screen: String,
+    image: String,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<SimulationResponse, BluePrintError> {
+    let screen = decode_blueprint_string(&screen)?;
+    let image = decode_blueprint_string(&image)?;
+    let report = simulate_screen(&screen, &image)?;
+    let preview = general_purpose::STANDARD.encode(encode_png(&report.image)?);
+    let warnings = import_warnings(&[&screen, &image], &app_data(&state));
+    Ok(SimulationResponse {
+        report,
+        preview,
+        warnings,
+    })
+}
+
+/// 解码任意蓝图字符串，返回格式化的JSON和概要
+#[tauri::command]
+pub fn inspect_blueprint(
+    blueprint: String,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<BlueprintInspection, BluePrintError> {
+    crate::core::inspect_blueprint(&blueprint, app_data(&state).game_version)
+}
+
+/// 获取启动时加载信号字典的错误，为空表示加载正常
+#[tauri::command]
+pub fn get_startup_error(state: State<'_, RwLock<AppData>>) -> Option<BluePrintError> {
+    app_data(&state).startup_error.clone()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalDictInfo {
+    signal_count: usize,
+    /// 当前配置下单个显示屏的像素上限
+    capacity: u32,
+    profile: SignalProfile,
+    excluded_signals: Vec<String>,
+    /// 生成的蓝图描述中记录的信号字典哈希
+    signal_hash: String,
+    /// 生成的蓝图记录的游戏版本，如 2.0.28
+    game_version: String,
+    encode_options: EncodeOptions,
+}
+
+impl From<&AppData> for SignalDictInfo {
+    fn from(data: &AppData) -> Self {
+        Self {
+            signal_count: data.signals.len(),
+            capacity: data.pixel_capacity(),
+            profile: data.profile.clone(),
+            excluded_signals: data.excluded_signals.clone(),
+            signal_hash: data.signal_hash(),
+            game_version: data.game_version.to_string(),
+            encode_options: data.encode_options,
+        }
+    }
+}
+
+/// 替换当前使用的信号字典并返回其信息，沿用当前的信号配置和排除列表
+fn replace_signal_dict(
+    state: &State<'_, RwLock<AppData>>,
+    mut data: AppData,
+) -> Result<SignalDictInfo, BluePrintError> {
+    let mut current = state.write().unwrap_or_else(|e| e.into_inner());
+    data.set_profile(current.profile.clone())?;
+    data.set_excluded_signals(current.excluded_signals.clone())?;
+    data.reference_dict = current.reference_dict.take();
+    data.game_version = current.game_version;
+    data.encode_options = current.encode_options;
+    let info = SignalDictInfo::from(&data);
+    *current = data;
+    Ok(info)
+}
+
+/// 获取当前信号字典的信息
+#[tauri::command]
+pub fn get_signal_dict_info(state: State<'_, RwLock<AppData>>) -> SignalDictInfo {
+    SignalDictInfo::from(&*app_data(&state))
+}
+
+/// 获取内置的信号配置
+#[tauri::command]
+pub fn get_signal_profiles() -> Vec<SignalProfile> {
+    SignalProfile::builtins()
+}
+
+/// 切换信号配置，可以是内置配置或调整过品质的配置
+#[tauri::command]
+pub fn set_signal_profile(
+    profile: SignalProfile,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<SignalDictInfo, BluePrintError> {
+    let mut data = state.write().unwrap_or_else(|e| e.into_inner());
+    data.set_profile(profile)?;
+    Ok(SignalDictInfo::from(&*data))
+}
+
+/// 修改不作为像素信号的信号，不传列表时恢复默认列表
+#[tauri::command]
+pub fn set_excluded_signals(
+    excluded: Option<Vec<String>>,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<SignalDictInfo, BluePrintError> {
+    let excluded = excluded.unwrap_or_else(|| {
+        DEFAULT_EXCLUDED_SIGNALS
+            .iter()
+            .map(|v| v.to_string())
+            .collect()
+    });
+    let mut data = state.write().unwrap_or_else(|e| e.into_inner());
+    data.set_excluded_signals(excluded)?;
+    Ok(SignalDictInfo::from(&*data))
+}
+
+/// 从信号字典文件或 data-raw-dump.json 加载信号字典
+#[tauri::command]
+pub fn load_signal_dict(
+    path: String,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<SignalDictInfo, BluePrintError> {
+    let data = AppData::from_path(path)?;
+    replace_signal_dict(&state, data)
+}
+
+/// 恢复使用程序自带的信号字典
+#[tauri::command]
+pub fn reset_signal_dict(
+    app: AppHandle,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<SignalDictInfo, BluePrintError> {
+    let data = bundled_app_data(&app)?;
+    replace_signal_dict(&state, data)
+}
+
+/// 由常量运算器蓝图生成参考字典，之后生成的蓝图会按它检查信号，返回与当前字典的差异
+#[tauri::command]
+pub fn load_reference_blueprint(
+    blueprint: String,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<SignalDictDiff, BluePrintError> {
+    let dict = decode_blueprint_string(&blueprint)?;
+    let reference = parse_signal_dict(&signal_dict_from_blueprint(&dict)?)?;
+    let mut data = state.write().unwrap_or_else(|e| e.into_inner());
+    let diff = diff_signal_dict(&data.signals, &reference);
+    data.reference_dict = Some(reference);
+    Ok(diff)
+}
+
+/// 设置生成的蓝图记录的游戏版本，格式为 2.0.28
+#[tauri::command]
+pub fn set_game_version(
+    version: String,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<SignalDictInfo, BluePrintError> {
+    let version: GameVersion = version.parse()?;
+    let mut data = state.write().unwrap_or_else(|e| e.into_inner());
+    data.game_version = version;
+    Ok(SignalDictInfo::from(&*data))
+}
+
+/// 设置生成蓝图字符串时的压缩等级和是否省略默认值
+#[tauri::command]
+pub fn set_encode_options(
+    options: EncodeOptions,
+    state: State<'_, RwLock<AppData>>,
+) -> Result<SignalDictInfo, BluePrintError> {
+    if options.compression_level > 9 {
+        return Err(BluePrintError::InvalidInput("压缩等级应为0~9".to_string()));
+    }
+    let mut data = state.write().unwrap_or_else(|e| e.into_inner());
+    data.encode_options = options;
+    Ok(SignalDictInfo::from(&*data))
+}
+
+/// 不再按参考字典检查生成的蓝图
+#[tauri::command]
+pub fn clear_reference_dict(state: State<'_, RwLock<AppData>>) {
+    state
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .reference_dict = None;
 }
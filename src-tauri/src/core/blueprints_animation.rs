@@ -0,0 +1,349 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::AppData;
+
+use super::blueprints_funcs::{
+    constant_combinator, generate_screen_blueprint, image_rgb_list, image_sections,
+};
+use super::blueprints_image::{apply_palette, AlphaMode, ImageMode, ImageOptions};
+use super::blueprints_lib::{
+    ArithmeticCombinatorBehavior, ArithmeticConditions, BluePrint, ControlBehavior,
+    DeciderCombinatorBehavior, DeciderCondition, DeciderConditions, DeciderOutput, Entity,
+    LogisticFilter, LogisticSection, Position, SignalId, SignalNetworks, Wire,
+};
+use super::{BluePrintError, Result};
+
+/// 游戏每秒的刻数
+const TICKS_PER_SECOND: f64 = 60.0;
+
+/// 既没有指定也读不到帧延迟时，每帧持续的游戏刻数
+const DEFAULT_FRAME_DELAY: u32 = 6;
+
+/// 动画播放选项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationOptions {
+    /// 每帧持续的游戏刻数，为空时使用 GIF 自带的帧延迟
+    #[serde(default)]
+    pub frame_delay: Option<u32>,
+    /// 循环播放，否则播放一次后停在最后一帧
+    #[serde(default = "default_loop_animation")]
+    pub loop_animation: bool,
+}
+
+fn default_loop_animation() -> bool {
+    true
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            frame_delay: None,
+            loop_animation: true,
+        }
+    }
+}
+
+/// 读取动画的所有帧，并返回 GIF 首帧的延迟（游戏刻）
+///
+/// path 可以是 GIF 文件、按文件名排序的帧图片文件夹或单张图片。
+fn load_frames(path: &Path) -> Result<(Vec<DynamicImage>, Option<u32>)> {
+    if path.is_dir() {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let file = entry?.path();
+            if file.is_file() && ImageFormat::from_path(&file).is_ok() {
+                files.push(file);
+            }
+        }
+        files.sort();
+        let frames = files
+            .iter()
+            .map(image::open)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        return Ok((frames, None));
+    }
+
+    if ImageFormat::from_path(path).ok() == Some(ImageFormat::Gif) {
+        let decoder = GifDecoder::new(BufReader::new(File::open(path)?))?;
+        let frames = decoder.into_frames().collect_frames()?;
+        let delay = frames
+            .first()
+            .map(|frame| {
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                (numer as f64 / denom as f64 * TICKS_PER_SECOND / 1000.0).round() as u32
+            })
+            .filter(|delay| *delay > 0);
+        let frames = frames
+            .into_iter()
+            .map(|frame| DynamicImage::ImageRgba8(frame.into_buffer()))
+            .collect();
+        return Ok((frames, delay));
+    }
+
+    Ok((vec![image::open(path)?], None))
+}
+
+/// 生成朝北放置的判断运算器，输入在下方、输出在上方
+fn decider_combinator(
+    entity_number: u64,
+    position: Position,
+    conditions: DeciderConditions,
+) -> Entity {
+    let mut entity = Entity::new(entity_number, "decider-combinator", position);
+    entity.control_behavior = Some(ControlBehavior::DeciderCombinator(
+        DeciderCombinatorBehavior {
+            decider_conditions: conditions,
+        },
+    ));
+    entity
+}
+
+/// 生成朝北放置的算术运算器
+fn arithmetic_combinator(
+    entity_number: u64,
+    position: Position,
+    conditions: ArithmeticConditions,
+) -> Entity {
+    let mut entity = Entity::new(entity_number, "arithmetic-combinator", position);
+    entity.control_behavior = Some(ControlBehavior::ArithmeticCombinator(
+        ArithmeticCombinatorBehavior {
+            arithmetic_conditions: conditions,
+        },
+    ));
+    entity
+}
+
+/// 生成动画显示屏蓝图
+///
+/// 显示屏下方每一列放一帧：常量运算器保存该帧数据，上方的判断运算器在帧序号
+/// 等于该列时把数据输出到显示屏。帧序号由左侧的时钟给出：判断运算器每刻累加
+/// 计数 T，算术运算器计算 F = T / 帧延迟。
+#[allow(clippy::too_many_arguments)]
+pub fn generate_animation_blueprint<P: AsRef<Path>>(
+    path: P,
+    width: u32,
+    height: u32,
+    wire_type_list: Option<Vec<i32>>,
+    always_on: bool,
+    animation: &AnimationOptions,
+    options: &ImageOptions,
+    data: &AppData,
+) -> Result<BluePrint> {
+    if options.mode == ImageMode::Palette {
+        return Err(BluePrintError::InvalidInput(
+            "动画暂不支持调色板模式！".to_string(),
+        ));
+    }
+    let wire_type_list = match wire_type_list {
+        Some(list) if !list.is_empty() => list,
+        _ => vec![1],
+    };
+    let wire_type = wire_type_list[0] as u64;
+
+    let (frames, gif_delay) = load_frames(path.as_ref())?;
+    let Some(first_frame) = frames.first() else {
+        return Err(BluePrintError::InvalidInput("没有找到动画帧！".to_string()));
+    };
+    let height = options.output_height(first_frame, width, height);
+    let mut blueprint = generate_screen_blueprint(
+        width,
+        height,
+        Some(wire_type_list),
+        always_on,
+        ImageMode::Rgb,
+        options.alpha == AlphaMode::Off,
+        data,
+    )?;
+    let frame_delay = animation
+        .frame_delay
+        .or(gif_delay)
+        .unwrap_or(DEFAULT_FRAME_DELAY)
+        .max(1);
+    let period = frame_delay
+        .checked_mul(frames.len() as u32)
+        .and_then(|period| i32::try_from(period).ok())
+        .ok_or_else(|| BluePrintError::InvalidInput("动画总时长过长！".to_string()))?;
+
+    let clock_signal = SignalId::new("signal-T", Some("virtual"), None);
+    let frame_signal = SignalId::new("signal-F", Some("virtual"), None);
+    // 显示屏下方第一行
+    let row = height as f64;
+    // 显示屏左下角电灯的实体编号
+    let mut output_target = ((height as u64 - 1) * width as u64 + 1, wire_type);
+    let first_selector = blueprint.entities.len() as u64 + 1;
+    let mut previous_selector = None;
+
+    for (index, frame) in frames.iter().enumerate() {
+        let (mut pixel_list, transparent) = image_rgb_list(frame, width, height, options)?;
+        apply_palette(&mut pixel_list, width, options);
+
+        // 不循环时最后一帧在序号超出后保持显示
+        let comparator = if !animation.loop_animation && index + 1 == frames.len() {
+            "≥"
+        } else {
+            "="
+        };
+        let selector = blueprint.entities.len() as u64 + 1;
+        blueprint.entities.push(decider_combinator(
+            selector,
+            Position::new(index as f64, row + 0.5),
+            DeciderConditions {
+                conditions: vec![DeciderCondition {
+                    first_signal: Some(frame_signal.clone()),
+                    constant: Some(index as i32),
+                    comparator: Some(comparator.to_string()),
+                    first_signal_networks: Some(SignalNetworks::only(2)),
+                    ..Default::default()
+                }].into(),
+                outputs: vec![DeciderOutput {
+                    signal: SignalId::new("signal-everything", Some("virtual"), None),
+                    copy_count_from_input: Some(true),
+                    networks: Some(SignalNetworks::only(1)),
+                    ..Default::default()
+                }].into(),
+                ..Default::default()
+            },
+        ));
+        let frame_number = selector + 1;
+        blueprint.entities.push(constant_combinator(
+            frame_number,
+            Position::new(index as f64, row + 2.0),
+            image_sections(&pixel_list, &transparent, data),
+        ));
+
+        let wires = &mut blueprint.wires;
+        // 帧数据从红线输入，帧序号从绿线输入
+        wires.push(Wire::new(frame_number, 1, selector, 1));
+        if let Some(previous) = previous_selector {
+            wires.push(Wire::new(previous, 2, selector, 2));
+        }
+        // 输出端串联后接到显示屏
+        let (target, connector) = output_target;
+        wires.push(Wire::new(selector, wire_type + 2, target, connector));
+        output_target = (selector, wire_type + 2);
+        previous_selector = Some(selector);
+    }
+
+    // 时钟：常量运算器提供 T=1，判断运算器输出端用绿线接回输入端，每刻累加
+    let counter = blueprint.entities.len() as u64 + 1;
+    let limit = if animation.loop_animation {
+        period
+    } else {
+        i32::MAX
+    };
+    blueprint.entities.push(decider_combinator(
+        counter,
+        Position::new(-2.0, row + 0.5),
+        DeciderConditions {
+            conditions: vec![DeciderCondition {
+                first_signal: Some(clock_signal.clone()),
+                constant: Some(limit),
+                comparator: Some("<".to_string()),
+                ..Default::default()
+            }].into(),
+            outputs: vec![DeciderOutput {
+                signal: clock_signal.clone(),
+                copy_count_from_input: Some(true),
+                ..Default::default()
+            }].into(),
+            ..Default::default()
+        },
+    ));
+    let pulse = counter + 1;
+    blueprint.entities.push(constant_combinator(
+        pulse,
+        Position::new(-2.0, row + 2.0),
+        vec![LogisticSection {
+            index: 1,
+            filters: vec![LogisticFilter {
+                index: 1,
+                name: clock_signal.name.clone(),
+                signal_type: clock_signal.signal_type.clone(),
+                comparator: Some("=".to_string()),
+                count: 1,
+                ..Default::default()
+            }].into(),
+            ..Default::default()
+        }],
+    ));
+    // 计数输出为 0 ~ 总时长-1，除以帧延迟即为帧序号
+    let divider = pulse + 1;
+    blueprint.entities.push(arithmetic_combinator(
+        divider,
+        Position::new(-1.0, row + 0.5),
+        ArithmeticConditions {
+            first_signal: Some(clock_signal),
+            second_constant: Some(frame_delay as i32),
+            operation: "/".to_string(),
+            output_signal: Some(frame_signal),
+            ..Default::default()
+        },
+    ));
+
+    let wires = &mut blueprint.wires;
+    wires.push(Wire::new(pulse, 1, counter, 1));
+    wires.push(Wire::new(counter, 4, counter, 2));
+    wires.push(Wire::new(counter, 4, divider, 2));
+    wires.push(Wire::new(divider, 4, first_selector, 2));
+
+    blueprint.version = Some(data.game_version);
+    Ok(blueprint)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, ImageBuffer, Rgba};
+
+    use super::*;
+
+    #[test]
+    fn test_gif_animation() {
+        let data = AppData::embedded().unwrap();
+        let gif_path = std::env::temp_dir().join("fbp_test_animation.gif");
+        let mut encoder = GifEncoder::new(File::create(&gif_path).unwrap());
+        for i in 0..3u8 {
+            let buffer = ImageBuffer::from_pixel(8, 6, Rgba([i * 80, 0, 0, 255]));
+            let delay = Delay::from_saturating_duration(Duration::from_millis(100));
+            encoder
+                .encode_frame(Frame::from_parts(buffer, 0, 0, delay))
+                .unwrap();
+        }
+        drop(encoder);
+
+        let blueprint = generate_animation_blueprint(
+            &gif_path,
+            4,
+            3,
+            None,
+            true,
+            &AnimationOptions::default(),
+            &ImageOptions::default(),
+            &data,
+        )
+        .unwrap();
+        // 12个电灯，每帧一对运算器，时钟3个运算器
+        assert_eq!(blueprint.entities.len(), 12 + 3 * 2 + 3);
+        let divider = blueprint.entities.last().unwrap();
+        let Some(ControlBehavior::ArithmeticCombinator(behavior)) = &divider.control_behavior
+        else {
+            panic!("最后一个实体应为算术运算器");
+        };
+        // 100毫秒为6刻
+        assert_eq!(behavior.arithmetic_conditions.second_constant, Some(6));
+        let numbers: Vec<u64> = blueprint.entities.iter().map(|e| e.entity_number).collect();
+        assert!(blueprint
+            .wires
+            .iter()
+            .all(|w| numbers.contains(&w.source_entity) && numbers.contains(&w.target_entity)));
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use super::blueprints_image::LAMP_PALETTE;
+use super::blueprints_lib::{
+    BluePrint, CircuitCondition, ControlBehavior, LampBehavior, LogisticFilter, SignalId,
+};
+
+/// 电路网络中区分信号的标识，类型缺省为 item，品质缺省为 normal
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SignalKey {
+    pub signal_type: String,
+    pub name: String,
+    pub quality: String,
+}
+
+impl SignalKey {
+    pub fn new(name: &str, signal_type: Option<&str>, quality: Option<&str>) -> Self {
+        Self {
+            signal_type: signal_type.unwrap_or("item").to_string(),
+            name: name.to_string(),
+            quality: quality.unwrap_or("normal").to_string(),
+        }
+    }
+}
+
+impl From<&SignalId> for SignalKey {
+    fn from(value: &SignalId) -> Self {
+        Self::new(
+            &value.name,
+            value.signal_type.as_deref(),
+            value.quality.as_deref(),
+        )
+    }
+}
+
+impl From<&LogisticFilter> for SignalKey {
+    fn from(value: &LogisticFilter) -> Self {
+        Self::new(
+            &value.name,
+            value.signal_type.as_deref(),
+            value.quality.as_deref(),
+        )
+    }
+}
+
+/// 一组信号及其数值
+pub type Signals = HashMap<SignalKey, i64>;
+
+/// 常量运算器输出的信号，关闭的运算器和分组不输出
+pub fn constant_signals(behavior: &ControlBehavior) -> Signals {
+    let mut signals = Signals::new();
+    let ControlBehavior::ConstantCombinator(behavior) = behavior else {
+        return signals;
+    };
+    if behavior.is_on == Some(false) {
+        return signals;
+    }
+    for section in &behavior.sections.sections {
+        if section.active == Some(false) {
+            continue;
+        }
+        for filter in &section.filters {
+            *signals.entry(SignalKey::from(filter)).or_default() += filter.count as i64;
+        }
+    }
+    signals
+}
+
+/// 蓝图中所有常量运算器输出的信号之和
+pub fn blueprint_constant_signals(blueprint: &BluePrint) -> Signals {
+    let mut signals = Signals::new();
+    for behavior in blueprint
+        .entities
+        .iter()
+        .filter_map(|e| e.control_behavior.as_ref())
+    {
+        merge_signals(&mut signals, &constant_signals(behavior));
+    }
+    signals
+}
+
+/// 将 other 中的信号累加到 signals
+pub fn merge_signals(signals: &mut Signals, other: &Signals) {
+    for (key, value) in other {
+        *signals.entry(key.clone()).or_default() += value;
+    }
+}
+
+/// 由蓝图连线解析出的电路网络，只计算常量运算器的输出
+///
+/// 每个实体的每个接点（1 红、2 绿，运算器的 3、4 为输出端）属于一个网络。
+pub struct CircuitNetworks {
+    network_of: HashMap<(u64, u64), usize>,
+    signals: Vec<Signals>,
+}
+
+impl CircuitNetworks {
+    pub fn new(blueprint: &BluePrint) -> Self {
+        // 并查集合并连线两端的接点
+        let mut nodes: Vec<(u64, u64)> = Vec::new();
+        let mut index_of: HashMap<(u64, u64), usize> = HashMap::new();
+        let mut parent: Vec<usize> = Vec::new();
+        let mut node = |key: (u64, u64), parent: &mut Vec<usize>| -> usize {
+            *index_of.entry(key).or_insert_with(|| {
+                nodes.push(key);
+                parent.push(parent.len());
+                parent.len() - 1
+            })
+        };
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        let mut pairs = Vec::new();
+        for wire in &blueprint.wires {
+            let a = node((wire.source_entity, wire.source_connector), &mut parent);
+            let b = node((wire.target_entity, wire.target_connector), &mut parent);
+            pairs.push((a, b));
+        }
+        for (a, b) in pairs {
+            let (a, b) = (find(&mut parent, a), find(&mut parent, b));
+            parent[a] = b;
+        }
+
+        let mut network_of = HashMap::new();
+        let mut network_index = HashMap::new();
+        for (i, key) in nodes.iter().enumerate() {
+            let root = find(&mut parent, i);
+            let next = network_index.len();
+            network_of.insert(*key, *network_index.entry(root).or_insert(next));
+        }
+
+        let mut signals = vec![Signals::new(); network_index.len()];
+        for entity in &blueprint.entities {
+            let Some(behavior) = &entity.control_behavior else {
+                continue;
+            };
+            let output = constant_signals(behavior);
+            if output.is_empty() {
+                continue;
+            }
+            for connector in [1, 2] {
+                if let Some(network) = network_of.get(&(entity.entity_number, connector)) {
+                    merge_signals(&mut signals[*network], &output);
+                }
+            }
+        }
+
+        Self {
+            network_of,
+            signals,
+        }
+    }
+
+    /// 实体从红、绿两个输入接点读到的信号之和
+    pub fn input_signals(&self, entity_number: u64) -> Signals {
+        let mut signals = Signals::new();
+        for connector in [1, 2] {
+            if let Some(network) = self.network_of.get(&(entity_number, connector)) {
+                merge_signals(&mut signals, &self.signals[*network]);
+            }
+        }
+        signals
+    }
+}
+
+/// 读取信号值，不存在时为0
+fn signal_value(signals: &Signals, signal: &SignalId) -> i64 {
+    signals
+        .get(&SignalKey::from(signal))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// 判断电路条件是否满足，无法识别的条件视为满足
+fn check_condition(condition: &CircuitCondition, signals: &Signals) -> bool {
+    let Some(first) = &condition.first_signal else {
+        return true;
+    };
+    let left = signal_value(signals, first);
+    let right = match (&condition.second_signal, condition.constant) {
+        (Some(second), _) => signal_value(signals, second),
+        (None, constant) => constant.unwrap_or_default() as i64,
+    };
+    match condition.comparator.as_deref().unwrap_or("<") {
+        "<" => left < right,
+        ">" => left > right,
+        "=" => left == right,
+        "≥" | ">=" => left >= right,
+        "≤" | "<=" => left <= right,
+        "≠" | "!=" => left != right,
+        _ => true,
+    }
+}
+
+/// 电灯读到给定信号时显示的颜色，熄灭时返回空
+pub fn lamp_color(behavior: Option<&LampBehavior>, signals: &Signals) -> Option<[u8; 3]> {
+    let Some(behavior) = behavior else {
+        return Some([255, 255, 255]);
+    };
+    if behavior.circuit_enabled == Some(true) {
+        let condition = behavior.circuit_condition.clone().unwrap_or_default();
+        if !check_condition(&condition, signals) {
+            return None;
+        }
+    }
+    if behavior.use_colors != Some(true) {
+        return Some([255, 255, 255]);
+    }
+
+    let channel = |signal: &Option<SignalId>| {
+        signal
+            .as_ref()
+            .map(|s| signal_value(signals, s).clamp(0, 255) as u8)
+            .unwrap_or_default()
+    };
+    match behavior.color_mode.unwrap_or_default() {
+        // 打包RGB
+        2 => {
+            let value = behavior
+                .rgb_signal
+                .as_ref()
+                .map(|s| signal_value(signals, s))
+                .unwrap_or_default();
+            Some([(value >> 16) as u8, (value >> 8) as u8, value as u8])
+        }
+        // 分量
+        1 => Some([
+            channel(&behavior.red_signal),
+            channel(&behavior.green_signal),
+            channel(&behavior.blue_signal),
+        ]),
+        // 颜色映射，取第一个大于0的颜色信号，没有时为白色
+        _ => LAMP_PALETTE
+            .iter()
+            .filter_map(|color| color.signal.map(|name| (name, color.rgb)))
+            .find(|(name, _)| {
+                signals
+                    .get(&SignalKey::new(name, Some("virtual"), None))
+                    .is_some_and(|value| *value > 0)
+            })
+            .map(|(_, rgb)| rgb)
+            .or(Some([255, 255, 255])),
+    }
+}
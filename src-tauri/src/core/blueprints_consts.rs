@@ -1,4 +1,4 @@
-#![allow(dead_code)]
+#![allow(dead_code, clippy::upper_case_acronyms)]
 
 /// 品质枚举
 #[derive(Debug)]
@@ -24,6 +24,7 @@ impl QualityType {
 
 /// 方向枚举
 #[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DirectionType {
     NORTH,       // 北
     NORTH_EAST,  // 东北
@@ -48,4 +49,56 @@ impl DirectionType {
             DirectionType::NORTH_WEST => 14,
         }
     }
+
+    /// 按顺时针排列的八个方向
+    const ALL: [DirectionType; 8] = [
+        DirectionType::NORTH,
+        DirectionType::NORTH_EAST,
+        DirectionType::EAST,
+        DirectionType::SOUTH_EAST,
+        DirectionType::SOUTH,
+        DirectionType::SOUTH_WEST,
+        DirectionType::WEST,
+        DirectionType::NORTH_WEST,
+    ];
+
+    /// 由蓝图中的方向值转换，铁轨等使用的十六方向中的斜向返回空
+    pub fn from_value(value: i64) -> Option<Self> {
+        Self::ALL.into_iter().find(|direction| direction.value() == value)
+    }
+
+    fn index(&self) -> usize {
+        (self.value() / 2) as usize
+    }
+
+    /// 顺时针旋转若干个直角
+    pub fn rotate(&self, quarter_turns: u32) -> Self {
+        Self::ALL[(self.index() + quarter_turns as usize * 2) % 8]
+    }
+
+    /// 左右镜像，东西互换
+    pub fn flip_horizontal(&self) -> Self {
+        Self::ALL[(8 - self.index()) % 8]
+    }
+
+    /// 上下镜像，南北互换
+    pub fn flip_vertical(&self) -> Self {
+        Self::ALL[(12 - self.index()) % 8]
+    }
+}
+
+/// 拆除计划过滤模式
+#[derive(Debug)]
+pub enum FilterMode {
+    WHITELIST, // 白名单
+    BLACKLIST, // 黑名单
+}
+
+impl FilterMode {
+    pub fn value(&self) -> u8 {
+        match self {
+            FilterMode::WHITELIST => 0,
+            FilterMode::BLACKLIST => 1,
+        }
+    }
 }
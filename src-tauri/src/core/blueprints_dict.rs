@@ -0,0 +1,943 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::blueprints_lib::{parse_blueprint, BluePrint, ControlBehavior, SignalId};
+use super::{BluePrintError, Result};
+use crate::AppData;
+
+/// 对应 item 信号的物品原型类别
+const ITEM_TYPES: [&str; 21] = [
+    "item",
+    "ammo",
+    "armor",
+    "blueprint",
+    "blueprint-book",
+    "capsule",
+    "copy-paste-tool",
+    "deconstruction-item",
+    "gun",
+    "item-with-entity-data",
+    "item-with-inventory",
+    "item-with-label",
+    "item-with-tags",
+    "module",
+    "rail-planner",
+    "repair-tool",
+    "selection-tool",
+    "space-platform-starter-pack",
+    "spidertron-remote",
+    "tool",
+    "upgrade-item",
+];
+
+/// 对应 entity 信号的实体原型类别，只有不能由物品放置的实体才有单独的信号
+const ENTITY_TYPES: [&str; 47] = [
+    "accumulator",
+    "ammo-turret",
+    "asteroid",
+    "assembling-machine",
+    "boiler",
+    "capture-robot",
+    "car",
+    "cargo-pod",
+    "character",
+    "cliff",
+    "combat-robot",
+    "construction-robot",
+    "container",
+    "electric-energy-interface",
+    "electric-pole",
+    "electric-turret",
+    "entity-ghost",
+    "fluid-turret",
+    "furnace",
+    "generator",
+    "inserter",
+    "item-entity",
+    "item-request-proxy",
+    "lab",
+    "lamp",
+    "lightning",
+    "lightning-attractor",
+    "logistic-container",
+    "logistic-robot",
+    "mining-drill",
+    "plant",
+    "radar",
+    "reactor",
+    "resource",
+    "roboport",
+    "segmented-unit",
+    "simple-entity",
+    "simple-entity-with-force",
+    "simple-entity-with-owner",
+    "solar-panel",
+    "spider-unit",
+    "spider-vehicle",
+    "temporary-container",
+    "tile-ghost",
+    "turret",
+    "unit",
+    "unit-spawner",
+];
+
+/// 游戏中的全部品质，按等级排列
+const ALL_QUALITIES: [&str; 5] = ["normal", "uncommon", "rare", "epic", "legendary"];
+
+/// Space Age 中常量运算器可以设置的信号类型
+const SPACE_AGE_SIGNAL_TYPES: [&str; 8] = [
+    "item",
+    "fluid",
+    "virtual",
+    "recipe",
+    "entity",
+    "space-location",
+    "asteroid-chunk",
+    "quality",
+];
+
+/// Space Age、品质和高架铁路模组新增的信号，原版 2.0 中没有这些原型
+const SPACE_AGE_SIGNALS: [&str; 97] = [
+    // Gleba
+    "agricultural-*",
+    "artificial-*-soil",
+    "overgrowth-*-soil",
+    "yumako*",
+    "jellynut*",
+    "jelly",
+    "bio*",
+    "biter-egg",
+    "captive-biter-spawner",
+    "capture-robot-rocket",
+    "pentapod-egg",
+    "nutrients*",
+    "spoilage",
+    "burnt-spoilage",
+    "*-bacteria*",
+    "tree-seed",
+    "wood-processing",
+    "fish-breeding",
+    "gleba-spawner*",
+    "*-pentapod*",
+    "*-stomper-shell",
+    "*-stromatolite",
+    "cliff-gleba",
+    "boompuff",
+    "cuttlepop",
+    "funneltrunk",
+    "hairyclubnub",
+    "slipstack",
+    "stingfrond",
+    "teflilly",
+    // Vulcanus
+    "tungsten-*",
+    "calcite",
+    "foundry",
+    "big-mining-drill",
+    "metallurgic-science-pack",
+    "casting-*",
+    "molten-*",
+    "concrete-from-molten-iron",
+    "lava",
+    "coal-synthesis",
+    "simple-coal-liquefaction",
+    "acid-neutralisation",
+    "steam-condensation",
+    "*-demolisher*",
+    "*-volcanic-rock",
+    "vulcanus-chimney*",
+    "sulfuric-acid-geyser",
+    "cliff-vulcanus",
+    "crater-cliff",
+    // Fulgora
+    "holmium-*",
+    "scrap*",
+    "recycler",
+    "electromagnetic-*",
+    "electrolyte",
+    "supercapacitor",
+    "superconductor",
+    "lightning*",
+    "tesla*",
+    "fulgoran-ruin-*",
+    "fulgurite*",
+    "big-fulgora-rock",
+    "cliff-fulgora",
+    // Aquilo
+    "ice",
+    "ice-*",
+    "ammonia*",
+    "solid-fuel-from-ammonia",
+    "lithium*",
+    "fluorine*",
+    "fluoroketone-*",
+    "empty-fluoroketone-*",
+    "cryogenic-*",
+    "fusion-*",
+    "foundation",
+    "quantum-processor",
+    "railgun*",
+    "mech-armor",
+    "toolbelt-equipment",
+    "battery-mk3-equipment",
+    "heating-tower",
+    "carbon",
+    "carbon-fiber",
+    "promethium-*",
+    // 太空平台
+    "*asteroid*",
+    "space-platform-*",
+    "thruster*",
+    "advanced-thruster-*",
+    "cargo-bay",
+    "cargo-landing-pad",
+    "cargo-pod*",
+    "crusher",
+    "rocket-turret",
+    "rocket-fuel-from-jelly",
+    "turbo-*",
+    "wube-logo-space-platform",
+    // 品质和高架铁路
+    "quality-module*",
+    "rail-ramp",
+    "rail-support",
+];
+
+/// 默认排除的信号：通配信号、任意品质信号和蓝图参数不能放入常量运算器或电灯
+pub const DEFAULT_EXCLUDED_SIGNALS: [&str; 5] = [
+    "signal-everything",
+    "signal-anything",
+    "signal-each",
+    "signal-any-quality",
+    "parameter-*",
+];
+
+/// 信号名称是否匹配排除规则，`*` 匹配任意数量的字符
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = name.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// 信号字典配置，决定生成显示屏时可用的品质和信号类型
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalProfile {
+    pub name: String,
+    /// 可用的品质，像素按此顺序依次展开
+    pub qualities: Vec<String>,
+    /// 可用的信号类型，物品信号的类型为 item，为空时不限制
+    pub signal_types: Vec<String>,
+    /// 配置对应的游戏中没有的信号，支持 `*` 通配
+    #[serde(default)]
+    pub unavailable_signals: Vec<String>,
+}
+
+impl SignalProfile {
+    fn new(name: &str, qualities: &[&str], signal_types: &[&str], unavailable: &[&str]) -> Self {
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        Self {
+            name: name.to_string(),
+            qualities: strings(qualities),
+            signal_types: strings(signal_types),
+            unavailable_signals: strings(unavailable),
+        }
+    }
+
+    /// 内置配置：无品质的原版 2.0、Space Age 和不限制信号类型的模组
+    pub fn builtins() -> Vec<Self> {
+        vec![
+            Self::new(
+                "vanilla",
+                &ALL_QUALITIES[..1],
+                &SPACE_AGE_SIGNAL_TYPES[..5],
+                &SPACE_AGE_SIGNALS,
+            ),
+            Self::default(),
+            Self::new("modded", &ALL_QUALITIES, &[], &[]),
+        ]
+    }
+
+    /// 检查配置是否可用
+    pub fn validate(&self) -> Result<()> {
+        if self.qualities.is_empty() {
+            return Err(BluePrintError::SignalDictionary(format!(
+                "配置{}没有可用的品质",
+                self.name
+            )));
+        }
+        let mut seen = HashSet::new();
+        if let Some(quality) = self.qualities.iter().find(|q| !seen.insert(*q)) {
+            return Err(BluePrintError::SignalDictionary(format!(
+                "配置{}中品质{}重复",
+                self.name, quality
+            )));
+        }
+        Ok(())
+    }
+
+    /// 信号在配置对应的游戏中是否可用，字典中没有类型的信号为物品
+    pub fn allows(&self, signal: &DictSignal) -> bool {
+        let signal_type = signal.signal_type.as_deref().unwrap_or("item");
+        (self.signal_types.is_empty() || self.signal_types.iter().any(|t| t == signal_type))
+            && !self
+                .unavailable_signals
+                .iter()
+                .any(|pattern| matches_pattern(pattern, &signal.name))
+    }
+}
+
+impl Default for SignalProfile {
+    fn default() -> Self {
+        Self::new("space-age", &ALL_QUALITIES, &SPACE_AGE_SIGNAL_TYPES, &[])
+    }
+}
+
+impl FromStr for SignalProfile {
+    type Err = BluePrintError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::builtins()
+            .into_iter()
+            .find(|profile| profile.name == s)
+            .ok_or_else(|| BluePrintError::SignalDictionary(format!("未知的信号配置：{}", s)))
+    }
+}
+
+/// 判断JSON是否为 `factorio --dump-data` 导出的 data-raw-dump.json
+pub fn is_data_dump(value: &Value) -> bool {
+    ["item", "virtual-signal"]
+        .iter()
+        .any(|category| value.get(category).is_some_and(Value::is_object))
+}
+
+/// 原型是否被隐藏或为参数，这些原型不能作为信号使用
+fn is_excluded(prototype: &Value) -> bool {
+    let hidden = prototype["hidden"].as_bool() == Some(true)
+        || prototype["flags"]
+            .as_array()
+            .is_some_and(|flags| flags.iter().any(|flag| flag == "hidden"));
+    hidden || prototype["parameter"].as_bool() == Some(true)
+}
+
+/// 某一类别下可用的原型
+fn prototypes<'a>(dump: &'a Value, category: &str) -> impl Iterator<Item = (&'a str, &'a Value)> {
+    dump.get(category)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter(|(_, prototype)| !is_excluded(prototype))
+        .map(|(name, prototype)| (name.as_str(), prototype))
+}
+
+/// 由 data-raw-dump.json 生成信号字典
+///
+/// 收集物品、流体、虚拟信号、配方、实体、星球和小行星碎块，排除隐藏和参数原型，
+/// 按名称排序后编号，格式与内置的 signal_dict.json 相同。
+/// 与物品或流体同名的配方默认不显示在信号界面中，除非原型声明 `hide_from_signal_gui = false`。
+pub fn signal_dict_from_data_dump(dump: &Value) -> Result<Value> {
+    if !is_data_dump(dump) {
+        return Err(BluePrintError::SignalDictionary(
+            "不是有效的 data-raw-dump.json".to_string(),
+        ));
+    }
+
+    // (名称, 信号类型)，物品信号的类型为空
+    let mut signals: BTreeSet<(&str, Option<&str>)> = BTreeSet::new();
+    let mut item_names = HashSet::new();
+    let mut placed_entities = HashSet::new();
+    for category in ITEM_TYPES {
+        for (name, prototype) in prototypes(dump, category) {
+            signals.insert((name, None));
+            item_names.insert(name);
+            if let Some(entity) = prototype["place_result"].as_str() {
+                placed_entities.insert(entity);
+            }
+        }
+    }
+    let mut fluid_names = HashSet::new();
+    for (name, _) in prototypes(dump, "fluid") {
+        signals.insert((name, Some("fluid")));
+        fluid_names.insert(name);
+    }
+    for (name, prototype) in prototypes(dump, "virtual-signal") {
+        // signal-everything 等特殊信号不能放入常量运算器
+        if prototype["special_signal"].as_bool() != Some(true) {
+            signals.insert((name, Some("virtual")));
+        }
+    }
+    for (name, prototype) in prototypes(dump, "recipe") {
+        let visible = match prototype["hide_from_signal_gui"].as_bool() {
+            Some(hide) => !hide,
+            None => !item_names.contains(name) && !fluid_names.contains(name),
+        };
+        if visible {
+            signals.insert((name, Some("recipe")));
+        }
+    }
+    for category in ENTITY_TYPES {
+        for (name, _) in prototypes(dump, category) {
+            if !placed_entities.contains(name) && !item_names.contains(name) {
+                signals.insert((name, Some("entity")));
+            }
+        }
+    }
+    for category in ["space-location", "planet"] {
+        for (name, _) in prototypes(dump, category) {
+            signals.insert((name, Some("space-location")));
+        }
+    }
+    for (name, _) in prototypes(dump, "asteroid-chunk") {
+        signals.insert((name, Some("asteroid-chunk")));
+    }
+    for (name, _) in prototypes(dump, "quality") {
+        signals.insert((name, Some("quality")));
+    }
+
+    if signals.is_empty() {
+        return Err(BluePrintError::SignalDictionary(
+            "数据中没有可用的信号".to_string(),
+        ));
+    }
+    Ok(build_signal_dict(signals))
+}
+
+/// 按顺序编号生成信号字典
+fn build_signal_dict<'a>(signals: impl IntoIterator<Item = (&'a str, Option<&'a str>)>) -> Value {
+    let dict: Map<String, Value> = signals
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, signal_type))| {
+            let signal = match signal_type {
+                Some(signal_type) => json!({"name": name, "type": signal_type}),
+                None => json!({"name": name}),
+            };
+            (index.to_string(), signal)
+        })
+        .collect();
+    Value::Object(dict)
+}
+
+/// 字典中的一个信号，物品信号的类型为空
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct DictSignal {
+    pub name: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub signal_type: Option<String>,
+}
+
+impl DictSignal {
+    pub fn new(name: &str, signal_type: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            // 蓝图中的物品信号可能写明 item 类型
+            signal_type: signal_type.filter(|t| *t != "item").map(|t| t.to_string()),
+        }
+    }
+}
+
+impl From<&SignalId> for DictSignal {
+    fn from(value: &SignalId) -> Self {
+        Self::new(&value.name, value.signal_type.as_deref())
+    }
+}
+
+impl std::fmt::Display for DictSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.signal_type {
+            Some(signal_type) => write!(f, "{}（{}）", self.name, signal_type),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// 解析信号字典，返回按序号排列的信号
+///
+/// 字典可以是以序号为键的对象，也可以是数组。序号必须从0开始连续，
+/// 信号的名称和类型不能重复，否则之后所有像素对应的信号都会错位。
+pub fn parse_signal_dict(dict: &Value) -> Result<Vec<DictSignal>> {
+    let entries: Vec<(usize, &Value)> = match dict {
+        Value::Array(items) => items.iter().enumerate().collect(),
+        Value::Object(m) => {
+            let mut entries = m
+                .iter()
+                .map(|(key, value)| match key.parse::<usize>() {
+                    Ok(index) => Ok((index, value)),
+                    Err(_) => Err(BluePrintError::SignalDictionary(format!(
+                        "信号字典的键{}不是序号",
+                        key
+                    ))),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            entries.sort_by_key(|(index, _)| *index);
+            entries
+        }
+        _ => {
+            return Err(BluePrintError::SignalDictionary(
+                "信号字典必须是JSON对象或数组".to_string(),
+            ))
+        }
+    };
+    if entries.is_empty() {
+        return Err(BluePrintError::SignalDictionary("信号字典为空".to_string()));
+    }
+
+    let mut seen = HashSet::new();
+    let mut signals = Vec::with_capacity(entries.len());
+    for (expected, (index, value)) in entries.into_iter().enumerate() {
+        if index != expected {
+            return Err(BluePrintError::SignalDictionary(format!(
+                "缺少序号为{}的信号",
+                expected
+            )));
+        }
+        let Some(name) = value["name"].as_str() else {
+            return Err(BluePrintError::SignalDictionary(format!(
+                "缺少序号为{}的信号名称",
+                index
+            )));
+        };
+        let signal = DictSignal::new(name, value["type"].as_str());
+        if !seen.insert(signal.clone()) {
+            return Err(BluePrintError::SignalDictionary(format!(
+                "序号为{}的信号{}重复",
+                index, signal
+            )));
+        }
+        signals.push(signal);
+    }
+    Ok(signals)
+}
+
+/// 信号与像素对应关系的哈希，信号的内容、顺序或品质变化时都会改变
+///
+/// 使用 FNV-1a，不依赖标准库哈希的实现，不同版本的程序得到的结果相同。
+pub fn signal_hash<'a>(
+    signals: impl IntoIterator<Item = &'a DictSignal>,
+    qualities: &[String],
+) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    for signal in signals {
+        write(signal.name.as_bytes());
+        write(&[0]);
+        write(signal.signal_type.as_deref().unwrap_or("item").as_bytes());
+        write(&[0]);
+    }
+    // 分隔信号和品质
+    write(&[1]);
+    for quality in qualities {
+        write(quality.as_bytes());
+        write(&[0]);
+    }
+    format!("{:016x}", hash)
+}
+
+/// 蓝图描述中记录信号字典哈希的前缀
+const SIGNAL_HASH_PREFIX: &str = "信号字典：";
+
+/// 在蓝图描述中记录当前信号字典的哈希，用于之后匹配显示屏和图片蓝图
+pub fn stamp_signal_hash(blueprint: &mut BluePrint, data: &AppData) {
+    let line = format!("{}{}", SIGNAL_HASH_PREFIX, data.signal_hash());
+    blueprint.description = Some(match blueprint.description.take() {
+        Some(description) if !description.is_empty() => format!("{}\n{}", description, line),
+        _ => line,
+    });
+}
+
+/// 读取蓝图描述中记录的信号字典哈希
+pub fn blueprint_signal_hash(blueprint: &BluePrint) -> Option<&str> {
+    blueprint
+        .description
+        .as_deref()?
+        .lines()
+        .find_map(|line| line.strip_prefix(SIGNAL_HASH_PREFIX))
+}
+
+/// 由常量运算器蓝图生成信号字典
+///
+/// 收集蓝图中所有常量运算器设置的信号，忽略品质和数值，按名称排序后编号。
+/// 可以在游戏中把全部信号放入常量运算器后导出，得到目标游戏版本的参考字典。
+pub fn signal_dict_from_blueprint(blueprint_dict: &Value) -> Result<Value> {
+    let blueprint = parse_blueprint(blueprint_dict)?;
+    let signals: BTreeSet<DictSignal> = blueprint
+        .entities
+        .iter()
+        .filter_map(|entity| match &entity.control_behavior {
+            Some(ControlBehavior::ConstantCombinator(behavior)) => Some(behavior),
+            _ => None,
+        })
+        .flat_map(|behavior| &behavior.sections.sections)
+        .flat_map(|section| &section.filters)
+        .map(|filter| DictSignal::new(&filter.name, filter.signal_type.as_deref()))
+        .collect();
+    if signals.is_empty() {
+        return Err(BluePrintError::SignalDictionary(
+            "蓝图中没有常量运算器信号".to_string(),
+        ));
+    }
+    Ok(build_signal_dict(
+        signals
+            .iter()
+            .map(|s| (s.name.as_str(), s.signal_type.as_deref())),
+    ))
+}
+
+/// 信号类型发生变化的信号
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeChange {
+    pub name: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// 当前信号字典与参考字典的差异
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalDictDiff {
+    /// 参考字典中有而当前字典中没有的信号
+    pub added: Vec<DictSignal>,
+    /// 当前字典中有而参考字典中没有的信号
+    pub removed: Vec<DictSignal>,
+    /// 两边都只有一种类型但类型不同的信号
+    pub type_changed: Vec<TypeChange>,
+}
+
+impl SignalDictDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.type_changed.is_empty()
+    }
+}
+
+/// 同名信号的全部类型
+type SignalTypes = Vec<Option<String>>;
+
+/// 比较当前信号字典和参考字典
+pub fn diff_signal_dict(current: &[DictSignal], reference: &[DictSignal]) -> SignalDictDiff {
+    // 名称 -> (当前字典中的类型, 参考字典中的类型)
+    let mut types: BTreeMap<String, (SignalTypes, SignalTypes)> = BTreeMap::new();
+    for signal in current {
+        let entry = types.entry(signal.name.clone()).or_default();
+        entry.0.push(signal.signal_type.clone());
+    }
+    for signal in reference {
+        let entry = types.entry(signal.name.clone()).or_default();
+        entry.1.push(signal.signal_type.clone());
+    }
+
+    let mut diff = SignalDictDiff::default();
+    for (name, (current, reference)) in types {
+        if let ([from], [to]) = (current.as_slice(), reference.as_slice()) {
+            if from != to {
+                diff.type_changed.push(TypeChange {
+                    name,
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+            continue;
+        }
+        for signal_type in reference.iter().filter(|t| !current.contains(t)) {
+            diff.added
+                .push(DictSignal::new(&name, signal_type.as_deref()));
+        }
+        for signal_type in current.iter().filter(|t| !reference.contains(t)) {
+            diff.removed
+                .push(DictSignal::new(&name, signal_type.as_deref()));
+        }
+    }
+    diff
+}
+
+/// 蓝图中常量运算器和电灯使用的、参考字典中不存在的信号
+pub fn unknown_signals(blueprint: &BluePrint, reference: &[DictSignal]) -> Vec<DictSignal> {
+    let known: HashSet<&DictSignal> = reference.iter().collect();
+    let mut unknown = BTreeSet::new();
+    for behavior in blueprint
+        .entities
+        .iter()
+        .filter_map(|e| e.control_behavior.as_ref())
+    {
+        let signals: Vec<DictSignal> = match behavior {
+            ControlBehavior::ConstantCombinator(behavior) => behavior
+                .sections
+                .sections
+                .iter()
+                .flat_map(|section| &section.filters)
+                .map(|filter| DictSignal::new(&filter.name, filter.signal_type.as_deref()))
+                .collect(),
+            ControlBehavior::Lamp(behavior) => [
+                &behavior.rgb_signal,
+                &behavior.red_signal,
+                &behavior.green_signal,
+                &behavior.blue_signal,
+            ]
+            .into_iter()
+            .flatten()
+            .map(DictSignal::from)
+            .collect(),
+            _ => Vec::new(),
+        };
+        unknown.extend(signals.into_iter().filter(|s| !known.contains(s)));
+    }
+    unknown.into_iter().collect()
+}
+
+/// 按参考字典检查生成的蓝图，没有设置参考字典时不检查
+pub fn reference_warnings(blueprint: &BluePrint, data: &AppData) -> Vec<String> {
+    let Some(reference) = &data.reference_dict else {
+        return Vec::new();
+    };
+    let unknown = unknown_signals(blueprint, reference);
+    if unknown.is_empty() {
+        return Vec::new();
+    }
+    let mut names: Vec<String> = unknown.iter().take(10).map(|s| s.to_string()).collect();
+    if unknown.len() > names.len() {
+        names.push("等".to_string());
+    }
+    vec![format!(
+        "{}个信号在参考字典中不存在，目标游戏版本中可能无法使用：{}",
+        unknown.len(),
+        names.join("、")
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_dict_from_data_dump() {
+        let dump = json!({
+            "item": {
+                "iron-plate": {"name": "iron-plate"},
+                "small-lamp": {"name": "small-lamp", "place_result": "small-lamp"},
+                "parameter-0": {"name": "parameter-0", "parameter": true},
+                "secret": {"name": "secret", "hidden": true},
+            },
+            "fluid": {"water": {"name": "water"}},
+            "virtual-signal": {
+                "signal-A": {"name": "signal-A"},
+                "signal-each": {"name": "signal-each", "special_signal": true},
+            },
+            "recipe": {
+                "iron-plate": {"name": "iron-plate"},
+                "water-barrel": {"name": "water-barrel", "hide_from_signal_gui": false},
+                "wood-processing": {"name": "wood-processing"},
+            },
+            "lamp": {"small-lamp": {"name": "small-lamp"}},
+            "unit": {"small-biter": {"name": "small-biter"}},
+            "resource": {"water": {"name": "water"}},
+            "planet": {"nauvis": {"name": "nauvis"}},
+            "asteroid-chunk": {"metallic-asteroid-chunk": {"name": "metallic-asteroid-chunk"}},
+        });
+        let dict = signal_dict_from_data_dump(&dump).unwrap();
+        let signals: Vec<(String, String)> = dict
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|v| {
+                let signal_type = v["type"].as_str().unwrap_or("item");
+                (
+                    v["name"].as_str().unwrap().to_string(),
+                    signal_type.to_string(),
+                )
+            })
+            .collect();
+        let expected = [
+            ("iron-plate", "item"),
+            ("metallic-asteroid-chunk", "asteroid-chunk"),
+            ("nauvis", "space-location"),
+            ("signal-A", "virtual"),
+            ("small-biter", "entity"),
+            ("small-lamp", "item"),
+            ("water", "fluid"),
+            ("water", "entity"),
+            ("water-barrel", "recipe"),
+            ("wood-processing", "recipe"),
+        ];
+        assert_eq!(signals.len(), expected.len());
+        for (name, signal_type) in expected {
+            assert!(signals.contains(&(name.to_string(), signal_type.to_string())));
+        }
+        // 编号从0开始连续
+        assert_eq!(dict["0"]["name"], "iron-plate");
+        assert!(signal_dict_from_data_dump(&json!({"0": {"name": "a"}})).is_err());
+    }
+
+    #[test]
+    fn test_diff_signal_dict() {
+        let reference = signal_dict_from_blueprint(&json!({
+            "blueprint": {
+                "item": "blueprint",
+                "entities": [{
+                    "entity_number": 1,
+                    "name": "constant-combinator",
+                    "position": {"x": 0.5, "y": 0.5},
+                    "control_behavior": {"sections": {"sections": [{
+                        "index": 1,
+                        "filters": [
+                            {"index": 1, "name": "water", "type": "fluid", "quality": "normal", "comparator": "=", "count": 1},
+                            {"index": 2, "name": "iron-plate", "type": "item", "quality": "rare", "comparator": "=", "count": 1},
+                            {"index": 3, "name": "signal-A", "type": "virtual", "quality": "normal", "comparator": "=", "count": 1},
+                            {"index": 4, "name": "iron-plate", "quality": "normal", "comparator": "=", "count": 2},
+                        ]
+                    }]}}
+                }]
+            }
+        }))
+        .unwrap();
+        assert_eq!(reference["0"], json!({"name": "iron-plate"}));
+        assert_eq!(reference.as_object().unwrap().len(), 3);
+        let reference = parse_signal_dict(&reference).unwrap();
+
+        let current_dict = json!({
+            "0": {"name": "iron-plate"},
+            "1": {"name": "signal-A", "type": "item"},
+            "2": {"name": "nauvis", "type": "space-location"},
+        });
+        let current = parse_signal_dict(&current_dict).unwrap();
+        let diff = diff_signal_dict(&current, &reference);
+        assert_eq!(diff.added, vec![DictSignal::new("water", Some("fluid"))]);
+        assert_eq!(
+            diff.removed,
+            vec![DictSignal::new("nauvis", Some("space-location"))]
+        );
+        assert_eq!(
+            diff.type_changed,
+            vec![TypeChange {
+                name: "signal-A".to_string(),
+                from: None,
+                to: Some("virtual".to_string()),
+            }]
+        );
+        assert!(diff_signal_dict(&reference, &reference).is_empty());
+
+        let mut data = crate::AppData::from_signal_dict(current_dict).unwrap();
+        let blueprint = crate::core::blueprints_funcs::generate_screen_blueprint(
+            4,
+            2,
+            None,
+            true,
+            crate::core::ImageMode::Rgb,
+            false,
+            &data,
+        )
+        .unwrap();
+        assert!(reference_warnings(&blueprint, &data).is_empty());
+        data.reference_dict = Some(reference.clone());
+        let unknown = unknown_signals(&blueprint, &reference);
+        assert!(unknown.contains(&DictSignal::new("signal-A", None)));
+        assert_eq!(reference_warnings(&blueprint, &data).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_signal_dict() {
+        // 键按字符串排序时 "10" 在 "2" 之前，解析后仍按序号排列
+        let names: Vec<String> = (0..12).map(|i| format!("signal-{}", i)).collect();
+        let object: Map<String, Value> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (i.to_string(), json!({"name": name, "type": "virtual"})))
+            .collect();
+        let signals = parse_signal_dict(&Value::Object(object)).unwrap();
+        assert_eq!(signals[2].name, "signal-2");
+        assert_eq!(signals[10].name, "signal-10");
+        let array: Vec<Value> = names
+            .iter()
+            .map(|name| json!({"name": name, "type": "virtual"}))
+            .collect();
+        assert_eq!(parse_signal_dict(&Value::Array(array)).unwrap(), signals);
+
+        assert!(parse_signal_dict(&json!({"0": {"name": "a"}, "2": {"name": "b"}})).is_err());
+        assert!(parse_signal_dict(&json!({"0": {"name": "a"}, "x": {"name": "b"}})).is_err());
+        assert!(parse_signal_dict(&json!([{"name": "a"}, {"name": "a", "type": "item"}])).is_err());
+        assert!(parse_signal_dict(&json!([{"name": "a"}, {"name": "a", "type": "fluid"}])).is_ok());
+
+        // 哈希与实现无关，固定值可以在不同版本之间匹配蓝图
+        let qualities = vec!["normal".to_string()];
+        let hash = signal_hash(&signals, &qualities);
+        assert_eq!(hash, "461d5323e1275d09");
+        let mut reordered = signals.clone();
+        reordered.swap(0, 1);
+        assert_ne!(signal_hash(&reordered, &qualities), hash);
+        assert_ne!(signal_hash(&signals, &[]), hash);
+    }
+
+    #[test]
+    fn test_excluded_signals() {
+        assert!(matches_pattern("parameter-*", "parameter-0"));
+        assert!(matches_pattern("*-asteroid-*", "metallic-asteroid-chunk"));
+        assert!(matches_pattern("signal-each", "signal-each"));
+        assert!(!matches_pattern("signal-each", "signal-each-2"));
+        assert!(!matches_pattern("parameter-*", "signal-parameter-0"));
+        assert!(!matches_pattern("ab*ba", "aba"));
+
+        let mut data = crate::AppData::from_signal_dict(json!([
+            {"name": "iron-plate"},
+            {"name": "signal-each", "type": "virtual"},
+            {"name": "parameter-0"},
+            {"name": "signal-A", "type": "virtual"},
+        ]))
+        .unwrap();
+        assert_eq!(data.usable_signals, vec![0, 3]);
+        assert_eq!(data.pixel_capacity(), 10);
+
+        data.set_excluded_signals(vec!["signal-*".to_string()])
+            .unwrap();
+        assert_eq!(data.usable_signals, vec![0, 2]);
+        assert!(data.set_excluded_signals(vec!["*".to_string()]).is_err());
+        assert_eq!(data.excluded_signals, vec!["signal-*"]);
+    }
+
+    #[test]
+    fn test_signal_profile() {
+        let mut data = crate::AppData::from_signal_dict(json!({
+            "0": {"name": "iron-plate"},
+            "1": {"name": "water", "type": "fluid"},
+            "2": {"name": "nauvis", "type": "space-location"},
+            "3": {"name": "my-mod-signal", "type": "mod-type"},
+        }))
+        .unwrap();
+        assert_eq!(data.pixel_capacity(), 15);
+
+        data.set_profile("vanilla".parse().unwrap()).unwrap();
+        assert_eq!(data.usable_signals, vec![0, 1]);
+        assert_eq!(data.pixel_capacity(), 2);
+
+        data.set_profile("modded".parse().unwrap()).unwrap();
+        assert_eq!(data.pixel_capacity(), 20);
+
+        let mut duplicated = SignalProfile::default();
+        duplicated.qualities.push("normal".to_string());
+        assert!(data.set_profile(duplicated).is_err());
+        let only_quality = SignalProfile {
+            signal_types: vec!["quality".to_string()],
+            ..Default::default()
+        };
+        assert!(data.set_profile(only_quality).is_err());
+        assert_eq!(data.profile.name, "modded");
+        assert!("unknown".parse::<SignalProfile>().is_err());
+    }
+}
@@ -1,162 +1,417 @@
 #![allow(dead_code)]
 
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
+use std::str::FromStr;
 
-use anyhow::Result;
-use image::imageops::FilterType;
 use image::DynamicImage;
-use serde_json::{json, Value};
+use serde::{Deserialize, Serialize};
 
 use crate::AppData;
 
-use super::blueprints_lib::{blueprint_to_dict, dict_to_blueprint};
-use super::BluePrintError;
+use super::blueprints_consts::FilterMode;
+use super::blueprints_dict::stamp_signal_hash;
+use super::blueprints_image::{
+    apply_palette, quantize_lamp_palette, resize_image, split_alpha, AlphaMode, ImageMode,
+    ImageOptions, LAMP_PALETTE,
+};
+use super::blueprints_lib::{
+    BluePrint, BlueprintBook, BlueprintRecord, CircuitCondition, ConstantCombinatorBehavior,
+    ControlBehavior, DeconstructionPlanner, DeconstructionSettings, Entity, Icon, LampBehavior,
+    LogisticFilter, LogisticSection, LogisticSections, PlannerFilter, Position, SignalId,
+    UpgradeMapper, UpgradePlanner, UpgradeSettings, UpgradeTarget, Wire,
+};
+use super::{BluePrintError, Result};
 
-/// 用于生成全物品名称枚举
-pub fn generate_all_signal_dict() {
-    // let bp = "0eNq1ncuO5Dh2ht8l16VB6K4owIvx/e6xPb6jMWAoGApOSaKKkjIrqlELLwzDKxvwwvAbjDdez85P0xj7LUwqLqIUITGq/jPoTXd25vdTJA95SB4efv+yK3veKFF3L++/fxG5rNuX9//w/UsripqV5mc1q/jL+xfzfzpWd14uq52oWSfVy5d3L6Le808v7/0v37174XUnOsHPgOE/Tj+r+2rHlf6Fd2ugdy+NbPXfytooal76o/jdy+nlffijWIvoP+mULH+240f2KvTv619qeW5+v53+uxa+lujdy0GUHVfzn16K8SblntdefuRtp/U/9qzU5dX/o5aq0l9uVKuGqaF8719+Y/hBb6rJ//LuxgtuPKEkTAtvtLbjvERxkYWTihXc0/X+4dt58Y3XKVa3jVSdt+MlUMLkRjywtvOosOkNyz81irctGTkbq6BXO0nG3d64vf6BKpT+lT3I9DfT2iUk+3cVTAgPZnVMiLYMrNEAPToAsGhavQTE+K5aCaDJrDoJkKN97XpVc+WJuuUKY46WRQAbzamUdeEdmf75nqCUwcykCIi+VZflBwpiYA37LCdBWnaj/6r0eKlnXCVyr5ElB7ijCVV8L/qKDDxa0k4UZNTRlNp+pyt3cFi+HTeaUSMapFzZBOR10jsPlwBSW1B3agxS8fxcuqv/pju+0EaFlTncPCVA8S3haGJNXzUAaLQsxUQJgMIJyFMMKlY0pbV9YzwSADhx9ETtafcRKV8yK995afHtvHTKy49DGVFqZs0Z+g9kJ16R/r21+rMqpPfGCmSwiKyZp+zFHuaNRsFUJ8qSqxPMDOxvBjijeWALlshaATVCe5F6FYl8X2x1kULoUSr3lNxJZImWTNfFqh9WsjB2NBGWm47sNUq+mhqA15TWXMPalhS9vVuvgsR4YzlXhwNexHi0GsU/9ppFwLRmFd3q2NAdzz21Eppa4sgaJUR3rLjp85ONm28lj7a01/P/0H0osJaLNnh9koibOvavvpVrmZN805XQvokuPwLA0Yi0XRbas6jYruR61cfZB2QNkIy2tBd6DclOXsNqDsy4iW/be829nXbRgdE+Cew2yhXvgPk7GQ3pyD4ztfcImJajxg+i5iTQ+A5KV+DRmkq9hD5oNwGApROP4yBywUrv1Ffsg/RaifjTyWhF8nXYK3rrjkTo7aNS/5yX5anuOxCebh6Vmwo+mpfI9Sxdsu6g/x4AjvZ1MCsycPmbjhaWl+Jw8PinppTGo2gBqG1iDRNKD1LImJJaGwm3g5JvpyX2pGf7e7p16hoZntPRuvpGD/t7TsDM7j/e20mJ1OdoTjvdu5HSZRv7tIRVHq8LPfwBQGs+kiVT6PyWBZYTnvdVX2K+QjYaTN3nJdclVJzlGHO0lyNnHbipk8VTGv+k1+Z1AbVyMmtls4mNNfNoKYe+HRZbcC1mc2TBteWBzb2d77JXojb7YnsFzcPb0W5u+6JEZH+y60oEHa1IHg7tUSo9skHbeNtwsh/4c2iC2EYzJ/ag24rlQP/cxrNTYJyY3Lc4Dk2n3oA6AazMmrXzUw7NC1vLYyv0p/alHjK0z9aZ9RVymmptJgipB7YhsAHgWSERrBk2aXbC7Ce0DXurMbQ187Qtr3alMcSK5Uc9cno+Qg7XyAFCjtbIIUIeDUqazfZhlYR0V39j7dodeSVy3b2MtwUd1ls7DLzWNnroC47wspnRy4oV9bB/AxfV2ttWJ6nnOgKoFa1Qsh0CsmchCbJGQypFceyGCU3JPYIMHyBzWZ43qxDw1JUzWHTIswISdtpHQlZ7vhWH0Dac771K7nvkNNa3whBsIjQKWWEIEyYy/ljRCPxgNhB4nZ/wz7diEe6wUB1YIQn3YKQirMiERhuRWfq+ag5BVYRrYKwyolU0VB2jdV0QBDWRLDCxSkiXqND3Z4shADvW6inFzNa60nPetno0Q5SWoxnY/pXVuTZ0MrGVyIZWVI2utFxqf6EUH3t+YDm2ieab4IalOApKmWBRRk82r6eh+nKll1Jg5YWLOsNsSaYTLTeSLMVer4/Myks7TV7DTewx7yuvYC0iGT8teftURC55Wu7WgohcumxiuVared/pVZhoGdoTl0eN8waRdlP2vMZ1lscMs51e8bLDuqAV0WFi0RGSP7F5hBRMdzMQVDgNjZcKokXWJzaNXiWDvHHu7RWrTeghCLSDnN68g2iPCC21OxsCyqatYE6BIN523g4oMd7MtrtgoP9goEN4ltdasiHoZ8egKG8rRqMvDz3EiiyfyUR1I9sa8ST+HD6L861gjJypHTQcx+m6n9Oe6u7IWwGVdzSWN2Z24nQ7K6zv2Dsl/Z4P7gtMtUIxJs4JAdl/4G7h1OCBs4hTrY2U3oSOmJgcnBrNjFOb++DA4OTYjuCUSn7gw4HBUVIUO3kMz2VJUfRl4yMylGTZq6M0nGWfjtiQ0s0T6xhcxX9iVYarLK/9CA0vXV75ERtiurz2ozfMNH5OjMZQ0+VFH6+azkR4k5hrmjp0CI02zRxa1Ka7degRGnC2cWgRmnHmu76LzpizwKFFbNJZ6NCjN+ws+hpJGvO24muG1VxhYoDejhyDJlOoWeF8QHjpfImYmwhkhGjFocFVuJ0fi5rDy1yovBfI8aUVVXPbUiag+vbpw2Vv2utrDDq64+eQORgY3seXEJGtO9bl6Xz4utPWe1DmhwDXvsnz5pn9QnOocY7H7KGNICvYRreYNn90/2G7PNdeBDQDquTlCfa6N0ZyMGIF5ly5QRgjl7E3D4AZAvTvgMMuec6RCLbACsfZ86bknR4bSPnLs981UHSQUZyiHYPN8sz3Qb4yxT9p69djwLHi2pe4SCJ68cPPQYj2xlSZC2S/Mdgs22cly47X3jCtng922CtDpDKX1GXGJRHbPvNdSOaDzXNfg0j4zlvm6EcETgX8K0KnxrB1jkhET1UUiccZ+PFzYqAnGviJU4d49g/8tW3q83Wss3ESmVD2ZO9DffDACmLq+rpoTeGhE7PAil+6Ec0xgdhDVP+eCp4oBXZGlVyxBkGt7HIZtHeOf8YmaCtm6SjLCj7cDKxIpSsQrlM7l4ruoObUvsciIAMrTOnMZA3TkzvGXLawy/1CEp8q2M6xLedI/paVKKTb/UKSklvZVcaLi2DZrQxginMYF87qFmFFd1+L0Gb7OTuWd1wJxGsLk/nuCwU0XVlg6OW+9vPb87TWNlKUDAlcD0I7BBenbZ8t+sXuKoZEbgRWVM+Nj+D8Z4u/E/JQ9p8QrWVXdtI7PXOlRryi2a+iFa922nOpBCP7YgBaWfF8wEY7TjIdWRCUc8dojP2DtVaCeIW8HSkgCts1hUtIDpIqbrPGRyN0gtifBcF4BwFdHAviZTs1F0U7ikHYilE6X0rjBeIkWGFKjR6yWCP3KHF5DWmiKWl8m3h5AWkC/Lyd9k72oMTTUysWUhjE2fMzCUGDr0TcV5WsBTN35lo9QAzpD1o+ILHxPdk8HfF8KQMi5rs+0CPZ/g+S4NkAAgkuG+2AKtEd9SoPgUVzGLxkTCb3ZepuPJeAVnh26NT58v45ExO4KW+nu+k7WbFzRx9uU3EwK0mQZPeZ6KjQ40q0EprA1IkKbaW7uV2bpUL7d9e06GrbSnyjh6vh+hMV2U5IzTSPimsn1u1YWfaqoOsiVlKc+dViKoXk8R1+Knz64AIzFTuze6Ku/WHso4Jv5558K0pkP8NKonNOU2ryfA3Jdxmy5WLFGp3fgXiZyTQSwgduvLkH1DEBJVIIsrl9XrNoeRQZsAIr+8653DuGrMWs8CBmMlNKsae4WR5YEUK56tsjVqWj7XVHTcMSk2fZUgO1euYyfixocdl2saudE2yex1cTyXapciTp9mZR7JIfnVRt2UiH9D2UUssGe+wLTqsVOlrsvAwnEotcLUaqFq+2GKlUst5ipFqpo8XkJ7HnJEqZq7nopLarbUWlE2426w1FJ+Q7WslydyjkAldTEeuFq+1FLBatNxqxWjxbDVhm6+XHHkmxHm7ml/xI4aOzMO3HOPmhm06HXwmeum8C41RBezfhWizVnR5NHGC4Glx11xMIvjH4Cj2qb1w+pJp3SIIPjJ4Vo/q62J0X5dfTXRO38K+nD6VPZIKhb9jMrXpdDIH7xaG//Qot86niM7ICC4Nx5DmvwEqZX9fF1/MF1r8iF7PDwHdqvPZlzuoeUgmcKkXJdwyRCJ0Sh74spIJEIqcI+9hD+0ZhEDs1zjmU25PuaJXH98hxZBgkbrnjkP9A926TiZAjzx9NHuZqO4mYo/U2V9vvruksi75GmNvJA0gYLLTCMHlbMpBmJd84yg6E2Q86VDvWeQTMcL6DWrK+zqE9rTC0rykx40wqLPNiaEWGHYTiTFVexQr2Gcp8HVrBYY3gKj9fqOrrfUtCT+9uzxDCs3nH0p0BOqELrSe6brVBxY7sffW6lgRE/z45i0fFDu5ajoocPig1tUb04CkzGBrPxgmElTyoBJxqHTB3sjJpifQgiQCzSULqXvHLbUu8qNuH4+MQhoE8U7iZz4Mw0Z9OhjBvNKxC8ZohVxvC2H4w5ey/E0CtyCwpWvOGOWtaKAlpaOWRakv5tpdvJFT78ZQDr4cnw3Dq8t7zVQWhL+8377VnqcB3J8J4u1J6zZcnqPjTN78uxSWodCvN1K2YFNhZtm5Pu01Q9dqPgA2JOGCgFZdhEujqBRKMjGcvyMHA5BHQqz4ECDS19iPzI17I7NFbPR7X69rGXDtG0Fs7yNN+IIYEbwVBTZ+foaH783yANNjgDqv7Aw06fIAOadBWwD8vO09UlclHQVQldrJE2X7gpYnDpEFbFsiVnpNZ6V2fBqURSFcEyBp2NNF6GI1fxdDdSdjWbVQpy6F1KbhWOJR5wKkw6wjByz0N3F+AU9W49eDYrVlL1uohfHBlWk6jEtpOQX5kquC0AvMnld4YtJSzwqIKKAI5zOwkv3skCWxohUGZkDvzWBZUstHYzDJIL94UtGKz0iSdexBM3G4erwFx8KOXzHFqMN8nxJHhbHGJE6O7BTDOHHf2h4fmZ6k+EXKyRB4StyPkdIl8y0OI0LMlOtHTAKEVKzRTIHgJILIihGZ0gsT/kRUWNC87fvkvsqKA5n3GziCIKIRLCkQnspEV7LOkgJ/DRptFu8XyAEWbRbMlyWESbVIHH82fE20WDfiazeJ6EQ0RWbThyx2J8oT4HpG/aMUPLtQhOr5DB2EvWvNwlQ1ygSI/XIVbyT8RkegpEZPuE1FZtuXLXbodWlmLRn29A6fdvgpqa1dQ73DNFBFwxvLCCushvCg+WI7c3fEjr2R3xDVcQbttIzpQwhmoS6CxHpxLIBC524JAJXa0xpu5uYK68FGQuFqESCddbRUikczdMkRKa+ZO8jBvFC6b/KV3UYi4bP5NiaIozd2oSy4GRCz4WjGvUbxiWJ69KAydXZzyI6OvVqP5yvX7PqSfmHydFM33uXwEc+Z4IPpAp7tAKbbuORAqRRtnFWoMkZbvrkI6scBRhXRKy2PJEO9LMCRH0XMS3tBmiJDLw9jrGbMU2B3eKHI6GDQy6/4FjcbysJCX4nBA0Nt1tAeHekfxxiEBBqxHse8QwOPuo3jlNr8a3qtBWyJetnBzfjWkmUPwy9bdlqJpO+gKehQv2/Shr2tedgq6rBbFK3d9mVCnvOx3db9DFJYtueOHUkA56KKVKKshB/JByRqZIFairHZSA3qocyYrNtx3Xckb2SD49Vv+rTl8NCdciMSKAZ8zObZDjphOD9fQHmiybMeXhOE0Mo7buWh1xU95NmAEc5Sse/Sv0gzcIoe/Jn3WKYQ/KHvS1YWFtuvtT1V16bLlX6dVLz+KquYmVEQij7xEqf+8lplPcj3xImNmGjyth6iEz38VuCefRs9LHdgeq7y1Gb8seoWNb2nixsMrkXTdeb/4vrAJZaufohVqT/Wixj9n+6TQedADlLLNs59kjpqOvEZuVkYrmbqmarrREJngSRltpbJtkUfsoyx8UssM5ohO9KTOK+tLZOzO4ieFWAdfKYiy5cHhevgocr7jqkC7RPq0ENpQ2dObIrr/qaaFxLbP743gaisZw6ZbJARSK1sBeJRVtJIijOrQey0z2OQ90oKfWmhHYCUt2DXSwXuFomSjlVxgQ9BYDaWsiKxgvStiDOe+kr4Zni7DtespTaAJgs+W8YpBJxZWpN4dmjdIHvrYCtO7Q5e84PWeqRPCH+33VaiuH/7y2v1FYYLFWX3yRu1vFgrmy/83gVR6vAntW5S8hnlW2Czfw7TYPsPdc2XelPWUOZmGsMlKmD1OTx8FTRuN4dEwnL885Z7/2yuOsu0QheV5VpiMI+axuiH9AKDhb9Y1FP/Y83Z4SOkTYjH+8tSq24bDdeUvz61v/c5kUymkN82fiqiFzoFmg+AjJ95H8LETHyD4xIkPEXzqxEcIPnPiYwS/deITAG8F3C3hUwTvnmAzBB848VsE77baHyN4t9X+JoJ3W+1vIXi31f42gndb7e8geLfV/i6Cd1vt7wH40G21v4/g3Vb7BwjebbV/iODdVvtHCN5ttX+M4N1W+ycI3m21f4rg3Vb7ZwjebbU/QfBuq/1zAB+5rfYvELzbav8Swbut9qcI3m21f4Xg3Vb71wjebbV/g+DdVvu3CN5ttX+H4N1W+/cI3m21+ZEjR2px7LbcPa+R5W7sNl5RHySi4LbfPXIPLI7XLPjIGrO9qzrzwhUiErlEjlKJz+adG0gmdsnsBStMNglEJHGJ5L16Rbac4tSpoGTbIgrZsxWFS22dHyMVFJMcJxuXBGIgie/8AKFyJN1XnKwYed94TCn5huDDVby6ZBgDRVZMnEhhxbqHzHxEMolDBhZIHQIlPxB8xoqJ0whsV3sViUbqnsGHmGSvFZ8RA0zd8/iRMyRsLU7dE3n7oUcie+LU7Yx/0/77d/qfL1++ezccFZjz+LLnjRLDoW/JdrzUP/vhn37xq3/5rx/+/R9/+OUv/++f//V//+fffvXf//HDf/5C/4r2H8xF7GGc20bbrV6TpGGQ+V++/D/Ly7+r"
-    let bp = "0eNq1XduSo7iW/Zd8Lk4YSYCpiHmY+33mzP0WHSdkLGOdxEALcJaro/59JLDT23aC3LnU0S/VWZVrgdCW9paWln552VSDao2u+5evv7zooqm7l6//98tLp8taVu5ntTyol68v7m96WfdR0Rw2upZ9Y15+fHnR9VZ9e/ka//jpy4uqe91rNQGM/3P6Qz0cNsrYf/BlCejLS9t09neb2jE6vFXyu+TLy+nlaxTnv0sskf213jTVHzZqL4/a/o79h50q3O90t3+25Jen+vKy01WvzP1Pz48ii2I4DNX5EX4eZGUf2f68bszBvrwjPbTSjH//9eVPxh8MrqXiH1/e4diXl/7UOjijCm3/QOD1NqrV0BsL3Mnx9T5Nwwlsr48qak1z1FtlomKvuv7zwGL++bdHWRdqGxXSbJpaF5HsbGM29q0KM3R7XZefp00+oNGmGDTwKqn/VQ6ql1UV9lUyP22jK/fBCtV1ENX6CapvtleEfL3cz9nvLYsljHaDqj7PFK9+BZV7T/1dAZEbk5GgNLoYqn6woRp1hVaWK2pl8Qqgs4/R++YNemj+3ka7atBb0kSHgw1SCUAvDAUTdmS78Kvq0c+ceF6hcB+hqQZsxIxT3+tQnqhTIyJGeR0KOtt7VFQ1xQXyQv3zoKsGoFhfoYzu93ZAs6PZzVz6WeScIPd6pwtt2+ePqqpO9dDbhtLAN2erj8BPw0G+Nih0fANdVcqcom5vnxvAZB9g9tKUtrXrMjLq0PQKgOcfwQ/GKGD2Y+ID0DdZIh2akWm669RhU7nXP8jCTikqigHgdAmYAcDZEjAHgEngvU+ujW3lAksfSdT1zcHG8sbGMpAnkkAbLKCcBrcgUxq/RtpG2s4KBBifT5s3srOtECpj4pwA9/a7nQAscY8VKTuctwdb7ACoyQPq4ZUFQU4/QOZBkK9RtlGygAqba0471YykJ6i9G2j30Ub3SM7Ecz9F1+oeIhErP8mbBYTHeUGiUFV9pA+HobY4Ib6rYPMvoUv0Owi+iP5QZgJMYpFpaz+HLcn30MskT1LYWcK0HZAtiPQGWbkpxxYUUdtUCGq2+Py7oSobM2X8AMl6keShGgeY8ltkXbt5f2s0kgYmq8XHvy21AZp4kcZOg7ah9no4hOBajvBO1lvwmyfLUQ4PtMlybHc26Wlt1LX2L2XbIE2VPEUElhpJ6mExchfmdZYD/thUhXSjL/j1lyM+yAyY5MscRpdlFaTN0tWvY7KxqmzKPRhgYE7J/K6bYi/HFfTPwzEKt6uGbwAWp1iVBOqUdH6xyUEPGzvDSSSVSZMlgrayQ6kuAPh0Cb4bqt2AfDSSX7ucK1IlUAOlSzHp0LtWvtVQJyOz7/uG0ucX1FePaLYsboBhKSNBZUtL5F2zhRm0sb8/7HYAOIkwC4Rv72SkZh2q10jXnTLQ/JuRenUwttuEwEzvMcMkcNn83ozj6W3Pt71BlsBwnV3XhQpZFRpZF8xyglXXbuEGSy7WKwLYjvuGgSJ+Hd8g2xnPJg6bpj9vEQDAjAAjD8gpzgZZoliLO6hop6E5eZ3cAd5sSO6HGhjp1vMT02+xj7vOfgWdUUE2QWnEmbKJNhJY1Fvnd2iVLYHc0NNKIHHM5xPHiQVKS/PYjx45xYTUUJDn7A4X3E7I+UJn6catlaJpW+iRxZMUUSE3yPpJnniJtIEaK32KICqVNNHbXiHL8Xn2HJfLmYGxKV97aarmLdqqunMLmrb2HQqsmMpzL2M7/fTTmoXVUwxR30Slsb+/RbhiL5cdajEJBgl5O7YYvRtKqHnmV4NsaWtkAeWN8YpMzXt10G5L39Z2SPURr5JHzECakNX8Yk9RaaRwiFfZLVSkvrVV09mUr0NQ18sPfFknRihyD0VZqQ1CEK88BMfBLX0NSDsRLVFRTQKl0qhabpHYIRKiopFIVMcLE69Fjir986B2sgDlLwsKopGmO9W9LWc11NIkOpvDRtoabt/05QA9d0rlqYVRPfTZFibTM3q0M80hOjRVr2owTYiJIuhjbe2ngfNbYDcZOzHBWOchwjhSlE6p2GacBiDtGluYGm9JIifF00dU6sXYPQGYUMZEFXRGbAyEJ+7x7MwI9Ww2vyNyZrC9xGlOmkpjROk98JvG2mJ+86Owv+u+Hjj/svWcqtEW2VsVQRq3mC1MkgHgiWroHS5CNT4xX4jJgCyMomKb+jGRCxXm1JTKrV+gCSXRDV1BA2WUfCEkh76vVNu0CPw1ELf2E47nDULMLURAZHHp7OIau8a+4XzOulU7Zf8VhJ4/oNmRv+0GaOwXK4LaVjZF2EaDkbXb9Hdq66iA1oFjES+0iRu0T1CjCPYIF6JVrtG41Z2rF0sVja3eqRByp1iIBQJU5RuLhKLbjn2y0V5Dg52YLyAtRe/qaSguRfYBXogveY3Jozb9MP7mhah5qyM7CzRvCEHuIajUrodZiCDoYxajy30Amms1qXZOK29nipMtF7bYR0jYPCyiuY4TvgDMEeBrfL7L3lRdOim30zwiyMkj8m4wtSygFk4fUcPsZMZEvnOXX05MTXWCBqtkfQ94kGU9ni5B858kn4UOlAWlq3sGl2LBBwrjdD6DVYe2P0UB89iUebjsZ29M86r6xnb/oqm2ATj5r+HcN30ASuGh3Ct5PAVq0sTDVY3DdRiu1Md10TQF4Mo8XK1yA4KyyWMpuwB8aw/fpHdyW73uzDPOl3v43saSHeYhQiNla47SnRvTygZWiNySSI5uwQOd7ogzMp0HmROz+b2a6f+jct90EMM1+C8QV4oW0ePFRJakvjXdq50H7KAVpqHJrH7ZVInCCHTiLPsAG9XQxESUdEW91JOhnjx/5EAW9olWyeIZ1XWRrZlAsXhMdEoXVFvV1F3bGDsQqwppZSJVumAPbj1g2ueF0a9J9U52fQCVXUyETCNkiPZNbiHDNW56CxyyZa8xt9NGSXOIbEIqv9shFEGdnyR3uttHG6PUFhI+xUSrZCG7cTNGTSV6iHEuv0bgrpLuzIkBzQry+EPEyB3AR2DZx7DowYI4JzHniixU8BQTOdJd1TZm1liPy+fXfi/o0RHsEuni89NqBGHJ5mHxLG9BdfRbsOXPsjUVNBowojxa+jZ7ZLOWreJZVLit2Io911ZhyGhsn0Ybh1GwvDPuhwAumVTd7ARuLDMiPxrxkBPrjAiO5hxJYAUPW81vrp7B7Tw16DqSPbxCzRYUSbdkGw0FV/4kjR01mq6TkIHJ6kmuPaTFY3H8JM/BJirDAWFiTzJ1FhRqOv4skRtI9rZOhhpQPMl2lAOSobI4WSQaDCSvYHHqh8c/zdKoUNc2hTfQQQdGhE+7YUyGS7fQAtr5xfnsrDqRuKODB2S4JPqnC+SYvRagZVF8D3uuDRBMNoMZot5gRPBUQsIkxoR3mgNVpGxB+zRC40eqGEufo4BDk5gklbYqrUF1FSPeSLDqlRFHpHKocVssPj+77qU2p6IaNvWwQRhigvhdmvHsDagiZUTWtFeyj9S3Yi/t9IV0MKJuGjGxow+M6JocnEupQXdDRsyQpn0gaQ7QCMZnC9j3fSYEPXuEwysVPl+whmTJn2ApbL7+ipWpRNq0b6qDW4GGdK2MGCBdAEFhKyO+R/e95MyA+1GyBfsjl9iH9D9iCwZII1dA5x224IQ0coWyyWEL4qeRKKhRDlswSBrZMJ8UtmCNNMIH8mNhxBhJI9oWlswfdLPAtkdVPTZQENWTA3QxvbMACOJ1DsV3TxhROY2HIfFTCyyZF0PcUIQ5s8CI6GmEB70lGJE7BTuKypLsFhSbLIisaURDZwqiZQpxIpYtOAyd8UMcqGDp/NKPBbYzcg2fTmUpW+Ywtma0/c0N09+QNU3iQjS6EyNQ4haqHpBwWJAbvdsohzBAYGn6CNwpBX28eUnRa3OURn2LVG10sXc1/+UtEL5rXEI+UoxYDznnBie3RAKFSIIc3A4SbTKiAapkF2CHkmWzmWolj8hURMQ/D9LmENpptqT9qVSp6q2EtjqIAKg6q7Cx0jGbLR3fZYMIevYIhxd12XzpGJIlf4IlQOm4np8cR54ahI8f4YJ4ehNh0BXYII4vjKiB7O/vse2ZtZjv2VP9tMGkCWydLHy5QBSpl8KWERtlSnArcJ09TQRu0BFnowsymq8SyVDV2N9onAkYYldP5sem1C79RQ+EM6ISescMczCAEaVQ1dgg3Ns5XW0DaOkYkQoF9qxh86KhAL6YLE8e4fApYcGtKCTLddI8qGIPT/H5/CLMtAeOWp6zPPcxBFz246uVjy2E9Tlfxc/TwPbnnFgSndEDWaDzBXOiM1PAdVK+Ej62UCulfJX4qIKulfIFX6MzH2z7zRdURheOYNbffLV+lgyU0fNV7mcKZQHOY+/4EMSgm8feASKgSTdfUBvNsYUw6uYxJzwP9wpiNp58wUvpt7jEkMfJr+ALY+TJie/SxDGYMljux2OSLGiLIN29YIGgr4n6eMpZGjQV4fF8TX32iUKtMTmbFQwHY4ife4nJ/wpbseKM+cgwZy3OuKe9UHzxzAuEaavEK5Cq5XBEnNk4MW2qx1UgC6fDHMHjxL/pYfHwHenT4NdoroeicptY2A2XnM0HM2UINY4S+6YLPKz940TVVA8227YfEOkdCze+vcNPXR2+poJz/jwXfLsC5+JZNncODCFKniXCbe05T58lO9/ieZDYy11n62a36/aNsbP0cGgRyGtcuwVpo3bODhu5AJCYPzXH8TjiW78PdUcqJ0opgh7kklRORFP3N0SDiaqYD+zgd1FzwZ8lCzS0EoeoVnZdyJvXOfGHeq9LsKGIXBVns6yuqZ2367jxGNA1ixOTqHcWtwY8HvsNQrBeIAh1OaaYFfrfuGcgF3uuPoaE10F5Mp9nh2a6Lr61Wpli2ska6m0X4Mw0J5Kqd/SznS+8sEK8ozCdMyeyqVBW7pxIp1rd9Q30piQep3ut3HdHAEn8NbpzSjHUAY4T4dR03AUtmInnEwF0wwMCGt+Bdm+6L5C8hty/dp2QQKcWTiRQFnR7ts4MtLhBRFEX8KPb2kL953iaLAFH0HdLF6E5Ap1R6NLIw8EZPdscW8lXaLWEaKA+WI6HEzOijCLwgToJ0UmBWTrRSDmkP4LPdQ23MwTeb8mlbLeYUJclN7PdoSK9laigLETdT1s9btiBBltigWTkFppciPDJSKimIS5HDskGjHQnfXVZQ8tBxNhohLURj/Rv4ms0wsHPR2RLE+DQusQYQWQ3iNBlCpwolc5goN0LJ7ZFF0R4h4oIlB4WFI2EtoXWNFbe0EUfYlZkU/1TAd1dyYnCyKgteO6TE3HRtLgS4uQjJxKjC2qwY5WcaI2MaqU26ERIFEZngXuAlYF8QYwbxGKY5ySmzpU1gpYQNMy7jufpHRa6GL9wuRkhmNYywQMFPF/fY1dyqAtMbrNgKXQmsRhAk4vV6h6w01WDAMb3gOiALYgKqCuMbBGo+UXEETqaBlpo4VAQW6BOTYriIFcnCKLteRgbur1s1eh7jCS8gsh55igaA3kdCKLmmaMwDXLCRKzWXobBHKFmyn0MW+3845BsT8SrZ0ngFiOqnRmqfWP0d3evKPRGzEeDjBMx96EflendpX4Iid9vxHI5wZtNXpx9OjT0kSvP4LvOBNHcBFppFfFSLI/VTrRC4Nde+BiBz73wQJkv2MoLzxH42AsvEHjmhU8QeO6FTxF44YXPEPjEC79G4FMvfI7A+6P2TxF4f9T+GQLvj9o/B+C5P2r/AoH3R+1fIvD+qP0rBN4ftX+NwPuj9m8QeH/U/i0C74/av0Pg/VH79wi8P2r/AYH3R+0/AvDCH7X/hMD7o/afEXh/1P4egfdH7b8g8P6o/VcE3h+1/4bA+6P23xF4f9T+BwLvj9r/ROD9UftfAHzij9r/RuD9Ufs/CLw/av8XgfdHraxP0XUP49NE/vgt9gpZKheJP4a3qoZewh/GW+Soskj8gQzekSMSfzDvFbbem/gDWtc7ZAE49cd09zpAqw6pP667XhavUae/I6t8C/eydfrQVm4x1Mks9c+D2skCs+gS6fyR1K7S7fhGCDxZla6at/HOSljbJdL546ajVS16ilqkqYcg4CFqkWYeshBnqEW6fpoFPkItiBRoAg90gloQGdAEXEFiCZHFnlYJeCBbZMxDFuo8tli4WW1iCnocW2TCQwefxhaZL+TDHcYWWfokF7qQnWVeolBHsUXmi/8gJ7FFlvtowh3EFgt+TTNkIc5hCyKI6hp3Fhe8ZVsQPRQBDHHqQKy5f+dopOxOnTNNVNsSahpBYfUW1FKI9fwRtCv+JKVwoq8acUcV6/RpsgBm3mKdPU0XwABOLNwXd08X6ECKIHqtqfNdPHajEDcXCSLcuoO3aauzWwK1VoKItyaCMGJmQTRcXavUFpYMC+IORRGhnUQi2brBRLYPiXDL5gRbZXqDdYH0A7zIjvFNDzXnwsQ8pTL49RuCaLfwizFFTmMNPY2bEIXWVNTiDmYJcXG6OwBnZx55QJAXSmYH7YSczjANHHAScqXbhDvdg4wgijtEm5psQMiEQqoKFYcmq/QOcDeYWhbQM2Z3kKDtYLLgn9Q5m3g7rSHH55IViS53sxcAFa9uoZwxJTKbJHF8B4h/n5jdQb5BFwAlMQ2cxkh3+wTYK2PxANnLGmpHEjjD5iCLvbvQFFI/JVT9NGzsUAqOQMRjqBsq+6ERrPXseDxCu0WPAlkXSOL8Y0j4mHDC5i+HCM20sEJ0w1SqUwfNj+Tys25olSmkTTM1JN5NyJVnE6YdBocCxLxGHhhxC3ec9WpXaUgbnxB/oF51lQSPBSXkFrMJD10sSYgR0IiIjTXkIrN+b4YOy9b4rHnXBRys7RMeexncgqj+jr3H/KJrr201A26fJeSOs75pKndVfIiFm4RcdtYbOd0o2iJ4CcWru9HbArvYPuEkwoxS4J0QCbHk6Ye67Eb/NmnzYeR+v4S48ryjQne8JMSV5x0RTWCJF48rAZoILwkT4sEzYQb78ILdIQ/2L8xkUgFj8/mTkkNtIQ5QBkUMdkI+9ILUYGjR03PJklzIwoe4LyNZ0gxZjiDHABPitDO0pZHbcdGurrF+fo3HwfZwt6nFOCATT4iRzhVwjQDGD4CFfesGPguREL+cC/K4jluAsPwBFhszF24euxCE8M9KiHfOBTeYfVBCTHSGXo8WCmFWhBOi85nbpjkO7oK+oUNY5ldJLui2LteHWiF5d5I/zeJuP0EyhoXbzB6odnawgbji57mcnSAynSxcbfbA5a41t30Fe7dZ59k3ieUgRAA0QuEF+cKtZ6EYrmH+1jRQu85v8TnkMEMesfVxoAq+ZzFJ5yP4bdi4wal0CaoMcmtmQjQ9k/ckghXfYYE2oUk2v7VwJgjyDYnlz8V/E6uliNvPWTU4fcnoTff7yH4xaH2YuP58gN5gG2MJMf95140pC1meIl3bCN9hi9s3N6HVr85EBIwXYgw03nMe5Clv7hlUIepB4gx0fnGw6iFCGF3vdO16ANqWa/aIifkpJsQhaDM4H4GodN3pM74IP9n/fvz46ct4w6jDqwbVGj2u9ByVcf7j4/Sdizy3VXLG2Tr+8eP/Ab4lyOQ=";
-    let mut bp_dict = blueprint_to_dict(bp).unwrap();
+/// 显示屏会用到的实体，拆除计划只拆除这些实体
+const DISPLAY_ENTITY_LIST: [&str; 5] = [
+    "small-lamp",
+    "constant-combinator",
+    "arithmetic-combinator",
+    "decider-combinator",
+    "selector-combinator",
+];
 
-    let filter_list = bp_dict["blueprint"]["entities"][0]["control_behavior"]["sections"]["sections"][0]["filters"]
-        .as_array_mut()
-        .unwrap();
-    filter_list.sort_by_key(|x| x["name"].to_string());
-
-    let mut all_item_dict = Value::default();
-    for (index, item) in filter_list.iter().enumerate() {
-        let i = index.to_string();
-        all_item_dict[&i] = Value::default();
-        all_item_dict[&i]["name"] = item["name"].clone();
-        if !item["type"].is_null() {
-            all_item_dict[&i]["type"] = item["type"].clone();
-        }
+/// 常量运算器每个分组最多容纳的信号数量
+const SECTION_SIZE: usize = 1000;
+
+/// 检查显示屏尺寸，返回像素总数，上限由当前信号配置决定
+pub(super) fn check_dimensions(width: u32, height: u32, data: &AppData) -> Result<u32> {
+    if width == 0 || height == 0 {
+        return Err(BluePrintError::InvalidDimensions { width, height });
+    }
+    let pixel_count = width.saturating_mul(height);
+    let capacity = data.pixel_capacity();
+    if pixel_count > capacity {
+        return Err(BluePrintError::SignalCapacityExceeded {
+            required: pixel_count,
+            capacity,
+        });
     }
+    Ok(pixel_count)
+}
 
-    println!("{}", all_item_dict);
+/// 字典中序号对应的信号，不带品质
+fn dict_signal(index: usize, data: &AppData) -> SignalId {
+    let signal = &data.signals[index];
+    SignalId {
+        name: signal.name.clone(),
+        signal_type: signal.signal_type.clone(),
+        quality: None,
+    }
 }
 
-/// 生成一个包含全信号的常量运算器蓝图
-pub fn generate_all_item_constant_combinator_blueprint(data: &AppData) -> String {
-    let mut item_dict_list = vec![];
+/// 像素序号对应的信号，当前配置下的每个可用信号按品质依次展开
+fn pixel_signal(index: u32, data: &AppData) -> SignalId {
+    let qualities = &data.profile.qualities;
+    let quality_count = qualities.len() as u32;
+    let mut signal = dict_signal(data.usable_signals[(index / quality_count) as usize], data);
+    signal.quality = Some(qualities[(index % quality_count) as usize].clone());
+    signal
+}
 
-    let signal_dict = &data.signal_dict;
-    for i in 0..data.signal_dict_len {
-        let mut item = json!({
-            "comparator": "=",
-            "count": 1,
-            "index": i + 1,
-            "name": signal_dict[i.to_string()]["name"],
-            "quality": "normal"
+/// 由信号生成常量运算器中的单个信号
+fn signal_filter(index: u32, signal: SignalId, count: i32) -> LogisticFilter {
+    LogisticFilter {
+        index,
+        name: signal.name,
+        signal_type: signal.signal_type,
+        quality: signal.quality,
+        comparator: Some("=".to_string()),
+        count,
+        ..Default::default()
+    }
+}
+
+/// 将信号按每组1000个拆分为常量运算器的分组
+fn filter_sections(
+    signal_list: Vec<(SignalId, i32)>,
+    section_size: usize,
+) -> Vec<LogisticSection> {
+    let mut sections = Vec::new();
+    let mut signal_iter = signal_list.into_iter().peekable();
+    while signal_iter.peek().is_some() {
+        let filters = signal_iter
+            .by_ref()
+            .take(section_size)
+            .enumerate()
+            .map(|(index, (signal, count))| signal_filter(index as u32 + 1, signal, count))
+            .collect();
+        sections.push(LogisticSection {
+            index: sections.len() as u32 + 1,
+            filters,
+            ..Default::default()
         });
-        if !signal_dict[i.to_string()]["type"].is_null() {
-            item["type"] = signal_dict[i.to_string()]["type"].clone();
-        }
+    }
+    sections
+}
 
-        item_dict_list.push(item);
+/// 生成常量运算器实体
+pub(super) fn constant_combinator(
+    entity_number: u64,
+    position: Position,
+    sections: Vec<LogisticSection>,
+) -> Entity {
+    let mut entity = Entity::new(entity_number, "constant-combinator", position);
+    entity.control_behavior = Some(ControlBehavior::ConstantCombinator(ConstantCombinatorBehavior {
+        sections: LogisticSections {
+            sections: sections.into(),
+            ..Default::default()
+        },
+        is_on: None,
+    }));
+    entity
+}
+
+/// 由像素颜色生成常量运算器的信号分组，颜色打包为 0xRRGGBB
+///
+/// transparent 不为空时透明像素输出0使电灯熄灭，不透明的纯黑输出1以免同样熄灭。
+pub(super) fn image_sections(
+    pixel_list: &[[u8; 3]],
+    transparent: &[bool],
+    data: &AppData,
+) -> Vec<LogisticSection> {
+    let signal_list = pixel_list
+        .iter()
+        .enumerate()
+        .map(|(i, pixel)| {
+            let count = (pixel[0] as i32) << 16 | (pixel[1] as i32) << 8 | (pixel[2] as i32);
+            let count = match transparent.get(i) {
+                Some(true) => 0,
+                Some(false) => count.max(1),
+                None => count,
+            };
+            (pixel_signal(i as u32, data), count)
+        })
+        .collect();
+    filter_sections(signal_list, SECTION_SIZE)
+}
+
+/// 全信号常量运算器中信号的数值
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CountMode {
+    /// 所有信号使用同一数值
+    #[default]
+    Fixed,
+    /// 按顺序依次为 1..N，用于测试解码和枚举信号
+    Sequential,
+}
+
+impl FromStr for CountMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "fixed" => Ok(CountMode::Fixed),
+            "sequential" => Ok(CountMode::Sequential),
+            _ => Err(format!("未知的数值模式：{}", s)),
+        }
     }
+}
 
-    let dict = json!({
-        "blueprint": {
-            "entities": [{
-                "control_behavior": {
-                    "sections": {
-                        "sections": [{
-                            "filters": item_dict_list,
-                            "index": 1
-                        }]
-                    }
-                },
-                "entity_number": 1,
-                "name": "constant-combinator",
-                "position": {"x": 0, "y": 0}
-            }],
-            "icons": [{
-                "index": 1,
-                "signal": {"name": "constant-combinator"}
-            }],
-            "item": "blueprint"
+/// 全信号常量运算器选项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllSignalsOptions {
+    /// 包含的品质，每个信号按品质依次展开
+    #[serde(default = "default_all_signals_qualities")]
+    pub qualities: Vec<String>,
+    /// 包含的信号类型，物品信号为 item，为空时包含全部类型
+    #[serde(default)]
+    pub signal_types: Vec<String>,
+    /// 每个分组的信号数量（1~1000）
+    #[serde(default = "default_section_size")]
+    pub section_size: usize,
+    #[serde(default)]
+    pub count_mode: CountMode,
+    /// 固定数值模式下每个信号的数值
+    #[serde(default = "default_signal_count")]
+    pub count: i32,
+}
+
+fn default_all_signals_qualities() -> Vec<String> {
+    vec!["normal".to_string()]
+}
+
+fn default_section_size() -> usize {
+    SECTION_SIZE
+}
+
+fn default_signal_count() -> i32 {
+    1
+}
+
+impl Default for AllSignalsOptions {
+    fn default() -> Self {
+        Self {
+            qualities: default_all_signals_qualities(),
+            signal_types: Vec::new(),
+            section_size: SECTION_SIZE,
+            count_mode: CountMode::Fixed,
+            count: 1,
         }
+    }
+}
+
+/// 生成一个包含全信号的常量运算器蓝图，排除的信号不会放入
+pub fn generate_all_item_constant_combinator_blueprint(
+    options: &AllSignalsOptions,
+    data: &AppData,
+) -> Result<BluePrint> {
+    if options.qualities.is_empty() {
+        return Err(BluePrintError::InvalidInput("至少需要一种品质！".to_string()));
+    }
+    if !(1..=SECTION_SIZE).contains(&options.section_size) {
+        return Err(BluePrintError::InvalidInput(format!(
+            "每个分组的信号数量应为1~{}",
+            SECTION_SIZE
+        )));
+    }
+
+    let signals = data.signals.iter().enumerate().filter(|(_, signal)| {
+        let signal_type = signal.signal_type.as_deref().unwrap_or("item");
+        !data.is_excluded(signal)
+            && (options.signal_types.is_empty()
+                || options.signal_types.iter().any(|t| t == signal_type))
     });
-    dict_to_blueprint(&dict).unwrap()
+    let mut signal_list = vec![];
+    for (i, _) in signals {
+        for quality in &options.qualities {
+            let mut signal = dict_signal(i, data);
+            signal.quality = Some(quality.clone());
+            let count = match options.count_mode {
+                CountMode::Fixed => options.count,
+                CountMode::Sequential => signal_list.len() as i32 + 1,
+            };
+            signal_list.push((signal, count));
+        }
+    }
+    if signal_list.is_empty() {
+        return Err(BluePrintError::InvalidInput("没有符合条件的信号！".to_string()));
+    }
+
+    Ok(BluePrint {
+        label: Some(format!("全部信号（{}）", signal_list.len())),
+        icons: vec![Icon {
+            index: 1,
+            signal: SignalId::new("constant-combinator", None, None),
+        }].into(),
+        entities: vec![constant_combinator(
+            1,
+            Position::new(0.0, 0.0),
+            filter_sections(signal_list, options.section_size),
+        )].into(),
+        version: Some(data.game_version),
+        ..Default::default()
+    })
 }
 
-/// 参数化生成彩色显示屏
-pub fn generate_screen_blueprint(
+/// 显示屏面板，坐标和尺寸均以电灯为单位
+#[derive(Debug, Clone, Copy)]
+struct Panel {
+    x: u32,
+    y: u32,
     width: u32,
     height: u32,
-    wire_type_list: Option<Vec<i32>>,
+}
+
+/// 将显示屏按行切分为若干面板，每块面板的像素数不超过上限
+fn split_panels(width: u32, height: u32, capacity: u32) -> Result<Vec<Panel>> {
+    if width == 0 || height == 0 {
+        return Err(BluePrintError::InvalidDimensions { width, height });
+    }
+    if width > capacity {
+        return Err(BluePrintError::SignalCapacityExceeded {
+            required: width,
+            capacity,
+        });
+    }
+
+    let panel_height = (capacity / width).min(height);
+    let panels = (0..height)
+        .step_by(panel_height as usize)
+        .map(|y| Panel {
+            x: 0,
+            y,
+            width,
+            height: panel_height.min(height - y),
+        })
+        .collect();
+    Ok(panels)
+}
+
+/// 在蓝图中放置一块面板的电灯并连线，每块面板都从第一个信号开始编号，
+/// 返回面板左上角电灯的实体编号
+fn push_screen_panel(
+    blueprint: &mut BluePrint,
+    panel: &Panel,
+    wire_type_list: &[i32],
     always_on: bool,
+    mode: ImageMode,
+    transparent_off: bool,
     data: &AppData,
-) -> Result<String> {
-    if width * height > 2985 {
-        return Err(BluePrintError("像素总和超过2985上限！".to_string()).into());
-    }
-    let mut dict = json!({
-        "blueprint": {
-            "entities": [],
-            "item": "blueprint",
-            "wires": [],
-        }
-    });
+) -> u64 {
+    let base = blueprint.entities.len() as u64;
+    let width = panel.width;
 
-    let signal_dict = &data.signal_dict;
-    let quality_list = &data.quality_list;
     for x in 0..width {
-        for y in 0..height {
-            let mut item = json!({
-                "entity_number": y * width + x + 1,
-                "name": "small-lamp",
-                "position": {"x": x, "y": y},
-                "control_behavior": {
-                    "color_mode": 2,
-                    "use_colors": true,
-                    "rgb_signal": {},
-                },
-                "always_on": always_on
-            });
-
+        for y in 0..panel.height {
             let index = y * width + x;
-            item["control_behavior"]["rgb_signal"]["name"] = signal_dict[(index / 5).to_string()]["name"].clone();
-            item["control_behavior"]["rgb_signal"]["quality"] = json!(quality_list[(index % 5) as usize].to_string());
-
-            if !signal_dict[(index / 5).to_string()]["type"].is_null() {
-                item["control_behavior"]["rgb_signal"]["type"] = signal_dict[(index / 5).to_string()]["type"].clone();
-            }
+            let mut entity = Entity::new(
+                base + (index + 1) as u64,
+                "small-lamp",
+                Position::new((panel.x + x) as f64, (panel.y + y) as f64),
+            );
+            let signal = pixel_signal(index, data);
+            // 信号为0（透明像素）时电灯熄灭，调色板模式总是按像素信号开关
+            let circuit_enabled = transparent_off || mode == ImageMode::Palette;
+            let circuit_condition = circuit_enabled.then(|| CircuitCondition {
+                first_signal: Some(signal.clone()),
+                constant: Some(0),
+                comparator: Some("≠".to_string()),
+                ..Default::default()
+            });
+            let behavior = match mode {
+                ImageMode::Rgb => LampBehavior {
+                    use_colors: Some(true),
+                    color_mode: Some(2),
+                    rgb_signal: Some(signal),
+                    ..Default::default()
+                },
+                ImageMode::Palette => palette_lamp_behavior(),
+            };
+            entity.control_behavior = Some(ControlBehavior::Lamp(LampBehavior {
+                circuit_enabled: circuit_enabled.then_some(true),
+                circuit_condition,
+                ..behavior
+            }));
+            entity.always_on = Some(always_on);
 
-            if let Some(entities) = dict["blueprint"]["entities"].as_array_mut() {
-                entities.push(item);
-            }
+            blueprint.entities.push(entity);
         }
     }
 
-    if let Some(wire_type_list) = wire_type_list {
-        for x in 0..width {
-            for y in 0..height {
-                let wires = dict["blueprint"]["wires"].as_array_mut().unwrap();
+    // 面板内电灯的实体编号
+    let lamp = |x: u64, y: u64| base + y * width as u64 + x + 1;
+    let wires = &mut blueprint.wires;
+    for x in 0..width as u64 {
+        for y in 0..panel.height as u64 {
+            for wire_type in [1, 2] {
+                if !wire_type_list.contains(&wire_type) {
+                    continue;
+                }
+                let wire_type = wire_type as u64;
                 // 连接最后一列电灯
-                if (x + 1) % width == 0 && y > 0 {
-                    if wire_type_list.contains(&1) {
-                        wires.push(json!([(y - 1) * width + x + 1, 1, y * width + x + 1, 1]));
-                    }
-                    if wire_type_list.contains(&2) {
-                        wires.push(json!([(y - 1) * width + x + 1, 2, y * width + x + 1, 2]));
-                    }
+                if x + 1 == width as u64 && y > 0 {
+                    wires.push(Wire::new(lamp(x, y - 1), wire_type, lamp(x, y), wire_type));
                 }
                 // 连接水平电灯
                 if x > 0 {
-                    if wire_type_list.contains(&1) {
-                        wires.push(json!([y * width + x, 1, y * width + x + 1, 1]));
-                    }
-                    if wire_type_list.contains(&2) {
-                        wires.push(json!([y * width + x, 2, y * width + x + 1, 2]));
-                    }
+                    wires.push(Wire::new(lamp(x - 1, y), wire_type, lamp(x, y), wire_type));
                 }
             }
         }
     }
 
-    dict_to_blueprint(&dict)
+    base + 1
+}
+
+/// 参数化生成彩色显示屏
+///
+/// RGB模式下电灯按像素信号的打包RGB值显示颜色。调色板模式下电灯在像素信号不为0时点亮，
+/// 颜色取网络中的颜色信号，整块显示屏为同一种颜色；多种颜色的图片请使用调色板模式的图片蓝图，
+/// 它自带电灯，无需配套的显示屏。
+/// transparent_off 为真时电灯在信号为0时熄灭，用于显示带透明像素的图片。
+pub fn generate_screen_blueprint(
+    width: u32,
+    height: u32,
+    wire_type_list: Option<Vec<i32>>,
+    always_on: bool,
+    mode: ImageMode,
+    transparent_off: bool,
+    data: &AppData,
+) -> Result<BluePrint> {
+    check_dimensions(width, height, data)?;
+    let mut blueprint = BluePrint::default();
+    let panel = Panel {
+        x: 0,
+        y: 0,
+        width,
+        height,
+    };
+    push_screen_panel(
+        &mut blueprint,
+        &panel,
+        &wire_type_list.unwrap_or_default(),
+        always_on,
+        mode,
+        transparent_off,
+        data,
+    );
+    stamp_signal_hash(&mut blueprint, data);
+    blueprint.version = Some(data.game_version);
+    Ok(blueprint)
 }
 
 /// 修改图片对比度
@@ -168,128 +423,631 @@ fn adjust_contrast(img: &DynamicImage, factor: f32) -> DynamicImage {
 
     // 对每个像素应用对比度调整
     for pixel in img.pixels_mut() {
-        let channels = pixel.0;
-        for i in 0..3 {
-            let new_value = f * (channels[i] as f32 - 128.0) + 128.0;
-            pixel.0[i] = new_value.clamp(0.0, 255.0) as u8;
+        for channel in pixel.0.iter_mut() {
+            let new_value = f * (*channel as f32 - 128.0) + 128.0;
+            *channel = new_value.clamp(0.0, 255.0) as u8;
         }
     }
 
     DynamicImage::ImageRgb8(img)
 }
 
-/// 获取一个图片的像素rgb列表
-fn get_image_rgb_list<P: AsRef<Path>>(img_path: P, width: u32, height: u32) -> Vec<[u8; 3]> {
-    // 打开图片
-    let img = image::open(img_path).unwrap();
-    // 调整大小，resize会按照原大小等比缩放，使用resize_exact强制缩放为指定大小
-    let enhanced_img = img.resize_exact(width, height, FilterType::Lanczos3);
+/// 将已解码的图片按选项缩放为指定大小，返回按行排列的像素rgb列表，
+/// 以及透明像素熄灭时每个像素是否透明
+pub(super) fn image_rgb_list(
+    img: &DynamicImage,
+    width: u32,
+    height: u32,
+    options: &ImageOptions,
+) -> Result<(Vec<[u8; 3]>, Vec<bool>)> {
+    if img.width() == 0 || img.height() == 0 {
+        return Err(BluePrintError::InvalidDimensions {
+            width: img.width(),
+            height: img.height(),
+        });
+    }
+    // 调整大小，默认拉伸为指定大小，也可以保持宽高比后填充或裁剪
+    let enhanced_img = resize_image(img, width, height, options);
     // 修改对比度
     // let enhanced_img = adjust_contrast(&enhanced_img, 1.5);
     // 获取像素
     let pixels = enhanced_img.to_rgba8();
+    Ok(split_alpha(&pixels, options))
+}
 
-    let mut rgb_list = Vec::new();
+/// 参数化生成图片蓝图
+///
+/// RGB模式只生成一个常量运算器，需要接到配套的显示屏上；调色板模式生成完整的显示屏，
+/// 电灯和驱动它们的运算器都已连好线，可以单独使用，always_on 只用于调色板模式的电灯。
+pub fn generate_image_blueprint<P: AsRef<Path>>(
+    img_path: P,
+    width: u32,
+    height: u32,
+    always_on: bool,
+    options: &ImageOptions,
+    data: &AppData,
+) -> Result<BluePrint> {
+    let img = image::open(img_path)?;
+    let height = options.output_height(&img, width, height);
+    let mut blueprint = if options.mode == ImageMode::Palette {
+        check_palette_dimensions(width, height)?;
+        let (pixel_list, transparent) = image_rgb_list(&img, width, height, options)?;
+        palette_image_blueprint(&pixel_list, &transparent, width, height, always_on, options)?
+    } else {
+        check_dimensions(width, height, data)?;
+        let (mut pixel_list, transparent) = image_rgb_list(&img, width, height, options)?;
+        apply_palette(&mut pixel_list, width, options);
+        BluePrint {
+            entities: vec![constant_combinator(
+                1,
+                Position::new(0.0, 0.0),
+                image_sections(&pixel_list, &transparent, data),
+            )]
+            .into(),
+            ..Default::default()
+        }
+    };
+    // 自动高度时在描述中注明实际尺寸，方便生成配套的显示屏
+    if options.auto_height {
+        blueprint.description = Some(format!("显示屏尺寸：{}x{}", width, height));
+    }
+    if options.mode == ImageMode::Rgb {
+        stamp_signal_hash(&mut blueprint, data);
+    }
+    blueprint.version = Some(data.game_version);
+    Ok(blueprint)
+}
+
+/// 电灯与常量运算器之间的最大连线距离
+const WIRE_REACH: i64 = 9;
+
+/// 调色板模式下显示屏的像素上限，避免生成游戏无法导入的超大蓝图
+const MAX_PALETTE_PIXEL_COUNT: u32 = 1 << 20;
+
+/// 检查调色板模式的显示屏尺寸
+fn check_palette_dimensions(width: u32, height: u32) -> Result<u32> {
+    if width == 0 || height == 0 {
+        return Err(BluePrintError::InvalidDimensions { width, height });
+    }
+    let pixel_count = width.saturating_mul(height);
+    if pixel_count > MAX_PALETTE_PIXEL_COUNT {
+        return Err(BluePrintError::SignalCapacityExceeded {
+            required: pixel_count,
+            capacity: MAX_PALETTE_PIXEL_COUNT,
+        });
+    }
+    Ok(pixel_count)
+}
+
+/// 按颜色信号显示的电灯
+fn palette_lamp_behavior() -> LampBehavior {
+    LampBehavior {
+        use_colors: Some(true),
+        color_mode: Some(0),
+        ..Default::default()
+    }
+}
 
-    for (_x, _y, pixel) in pixels.enumerate_pixels() {
-        let data = pixel.0;
-        if data.len() == 4 {
-            rgb_list.push([data[0], data[1], data[2]]);
+/// 并查集，用于合并同色电灯组成的电路网络
+struct DisjointSet(Vec<usize>);
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self((0..len).collect())
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.0[i] != i {
+            self.0[i] = self.0[self.0[i]];
+            i = self.0[i];
         }
+        i
     }
 
-    rgb_list
+    /// 合并两个集合，已在同一集合时返回false
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        self.0[a] = b;
+        a != b
+    }
 }
 
-/// 参数化生成图片蓝图
-pub fn generate_image_blueprint<P: AsRef<Path>>(
-    img_path: P,
+/// 调色板显示屏中给一个同色网络供电的常量运算器
+struct PaletteFeed {
+    position: (i64, i64),
+    color: usize,
+    /// 与运算器相连的像素
+    target: usize,
+    wire_type: u64,
+    /// 需要抵消的颜色信号，网络中有电灯替其它颜色中继时使用
+    suppressed: BTreeSet<usize>,
+}
+
+/// 生成调色板模式的图片显示屏，电灯和运算器已连好线，不需要配套的显示屏
+///
+/// 同色电灯用红线连成网络，每个网络由一个常量运算器输出该颜色信号，运算器放在显示屏外
+/// 或熄灭像素的位置。离这些位置太远的网络改用绿线，经由其它网络的电灯中继到显示屏边缘，
+/// 中继电灯所在网络的运算器输出 -1 抵消中继的颜色，使中继电灯仍显示自己的颜色。
+/// 熄灭和透明像素不放置电灯。
+fn palette_image_blueprint(
+    pixel_list: &[[u8; 3]],
+    transparent: &[bool],
     width: u32,
     height: u32,
-    data: &AppData,
-) -> Result<String> {
-    let pixel_count = width * height;
-    let pixel_list: Vec<[u8; 3]> = get_image_rgb_list(img_path, width, height);
+    always_on: bool,
+    options: &ImageOptions,
+) -> Result<BluePrint> {
+    let mut colors = quantize_lamp_palette(pixel_list, width, options.dither);
+    for (color, transparent) in colors.iter_mut().zip(transparent) {
+        if *transparent {
+            *color = 0;
+        }
+    }
+    let (w, h) = (width as i64, height as i64);
+    let pixel = |x: i64, y: i64| (y * w + x) as usize;
+    let coordinate = |i: usize| (i as i64 % w, i as i64 / w);
 
-    let mut item_dict_list = Vec::new();
-    let mut item_dict_cache = Vec::new();
-    
-    let signal_dict = &data.signal_dict;
-    let quality_list = &data.quality_list;
-    for i in 0..pixel_count {
-        if i % 1000 == 0 {
-            if item_dict_cache.len() > 0 {
-                item_dict_list.push(item_dict_cache.clone());
+    // 连线范围内的偏移，按距离从近到远排列
+    let mut offsets: Vec<(i64, i64)> = (-WIRE_REACH..=WIRE_REACH)
+        .flat_map(|dy| (-WIRE_REACH..=WIRE_REACH).map(move |dx| (dx, dy)))
+        .filter(|(dx, dy)| (dx, dy) != (&0, &0) && dx * dx + dy * dy <= WIRE_REACH * WIRE_REACH)
+        .collect();
+    offsets.sort_by_key(|(dx, dy)| dx * dx + dy * dy);
+
+    // 先连接相邻的同色电灯，再用长线合并连线范围内的同色网络
+    let mut networks = DisjointSet::new(colors.len());
+    let mut edges = Vec::new();
+    for y in 0..h {
+        for x in 0..w {
+            let i = pixel(x, y);
+            if colors[i] == 0 {
+                continue;
+            }
+            for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                if nx < w
+                    && ny < h
+                    && colors[pixel(nx, ny)] == colors[i]
+                    && networks.union(i, pixel(nx, ny))
+                {
+                    edges.push((i, pixel(nx, ny)));
+                }
             }
-            item_dict_cache.clear();
         }
-
-        let mut item = json!({
-            "comparator": "=",
-            "count": 1,
-            "index": i % 1000 + 1,
-            "name": signal_dict[(i / 5).to_string()]["name"].clone(),
-            "quality": quality_list[(i % 5) as usize]
-        });
-        if !signal_dict[(i / 5).to_string()]["type"].is_null() {
-            item["type"] = signal_dict[(i / 5).to_string()]["type"].clone();
+    }
+    for y in 0..h {
+        for x in 0..w {
+            let i = pixel(x, y);
+            if colors[i] == 0 {
+                continue;
+            }
+            for (dx, dy) in &offsets {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= w || ny >= h {
+                    continue;
+                }
+                let j = pixel(nx, ny);
+                if colors[j] == colors[i] && networks.union(i, j) {
+                    edges.push((i, j));
+                }
+            }
         }
+    }
 
-        let index = i as usize;
-        item["count"] = json!(
-            (pixel_list[index][0] as u32) << 16 | 
-            (pixel_list[index][1] as u32) << 8 | 
-            (pixel_list[index][2] as u32)
-        );
+    // 按网络分组
+    let mut group_of = vec![usize::MAX; colors.len()];
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_index = HashMap::new();
+    for (i, color) in colors.iter().enumerate() {
+        if *color != 0 {
+            let root = networks.find(i);
+            let next = groups.len();
+            let group = *group_index.entry(root).or_insert(next);
+            if group == groups.len() {
+                groups.push(Vec::new());
+            }
+            groups[group].push(i);
+            group_of[i] = group;
+        }
+    }
 
-        item_dict_cache.push(item);
+    // 显示屏外或熄灭像素上连线范围内最近的空位
+    let mut occupied = HashSet::new();
+    let find_free = |x: i64, y: i64, occupied: &HashSet<(i64, i64)>| {
+        offsets
+            .iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .find(|&(nx, ny)| {
+                let outside = nx < 0 || ny < 0 || nx >= w || ny >= h;
+                !occupied.contains(&(nx, ny)) && (outside || colors[pixel(nx, ny)] == 0)
+            })
+    };
 
-        if i == pixel_count - 1 {
-            if item_dict_cache.len() > 0 {
-                item_dict_list.push(item_dict_cache.clone());
+    // 够得着空位的网络直接用红线连接运算器
+    let mut feeds = Vec::new();
+    let mut feed_of = vec![None; groups.len()];
+    let mut islands = Vec::new();
+    for (group, members) in groups.iter().enumerate() {
+        let found = members.iter().find_map(|i| {
+            let (x, y) = coordinate(*i);
+            find_free(x, y, &occupied).map(|position| (position, *i))
+        });
+        match found {
+            Some((position, target)) => {
+                occupied.insert(position);
+                feed_of[group] = Some(feeds.len());
+                feeds.push(PaletteFeed {
+                    position,
+                    color: colors[target],
+                    target,
+                    wire_type: 1,
+                    suppressed: BTreeSet::new(),
+                });
             }
-            item_dict_cache.clear();
+            None => islands.push(group),
         }
     }
 
-    let mut dict = json!({
-        "blueprint": {
-            "entities": [
-                {
-                    "control_behavior": {
-                        "sections": {
-                            "sections": []
-                        }
-                    },
-                    "entity_number": 1,
-                    "name": "constant-combinator",
-                    "position": {"x": 0, "y": 0}
+    // 其余网络从离边缘最近的电灯出发，朝边缘经由其它网络的电灯用绿线中继
+    let mut is_island = vec![false; groups.len()];
+    let mut relayed = vec![false; colors.len()];
+    let mut relay_wires = Vec::new();
+    for group in islands {
+        is_island[group] = true;
+        let distance_to_edge = |i: usize| {
+            let (x, y) = coordinate(i);
+            x.min(y).min(w - 1 - x).min(h - 1 - y)
+        };
+        let start = *groups[group]
+            .iter()
+            .min_by_key(|i| distance_to_edge(**i))
+            .unwrap_or(&groups[group][0]);
+        let color = colors[start];
+        let (x, y) = coordinate(start);
+        let direction = [(-1, 0), (0, -1), (1, 0), (0, 1)]
+            .into_iter()
+            .zip([x, y, w - 1 - x, h - 1 - y])
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(direction, _)| direction)
+            .unwrap_or((-1, 0));
+
+        let mut current = start;
+        let position = loop {
+            let (cx, cy) = coordinate(current);
+            if let Some(position) = find_free(cx, cy, &occupied) {
+                break position;
+            }
+            let next = offsets
+                .iter()
+                .filter(|(dx, dy)| dx * direction.0 + dy * direction.1 > 0)
+                .map(|(dx, dy)| (cx + dx, cy + dy))
+                .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && nx < w && ny < h)
+                .map(|(nx, ny)| pixel(nx, ny))
+                .filter(|j| colors[*j] != 0 && feed_of[group_of[*j]].is_some() && !relayed[*j])
+                .max_by_key(|j| {
+                    let (nx, ny) = coordinate(*j);
+                    (nx - cx) * direction.0 + (ny - cy) * direction.1
+                });
+            let Some(next) = next else {
+                return Err(BluePrintError::InvalidInput(format!(
+                    "无法从像素({}, {})所在的同色区域向显示屏边缘中继信号！",
+                    x, y
+                )));
+            };
+            relayed[next] = true;
+            relay_wires.push((current, next));
+            if colors[next] != color {
+                if let Some(feed) = feed_of[group_of[next]] {
+                    feeds[feed].suppressed.insert(color);
                 }
-            ],
-            "item": "blueprint"
+            }
+            current = next;
+        };
+        occupied.insert(position);
+        feeds.push(PaletteFeed {
+            position,
+            color,
+            target: current,
+            wire_type: 2,
+            suppressed: BTreeSet::new(),
+        });
+    }
+
+    // 放置电灯，熄灭像素不放置
+    let mut blueprint = BluePrint::default();
+    let mut lamp_numbers = vec![0; colors.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let i = pixel(x, y);
+            if colors[i] == 0 {
+                continue;
+            }
+            let entity_number = blueprint.entities.len() as u64 + 1;
+            let mut entity = Entity::new(
+                entity_number,
+                "small-lamp",
+                Position::new(x as f64, y as f64),
+            );
+            entity.control_behavior = Some(ControlBehavior::Lamp(palette_lamp_behavior()));
+            entity.always_on = Some(always_on);
+            blueprint.entities.push(entity);
+            lamp_numbers[i] = entity_number;
         }
-    });
+    }
+    for (a, b) in edges {
+        let wire_type = if is_island[group_of[a]] { 2 } else { 1 };
+        blueprint.wires.push(Wire::new(
+            lamp_numbers[a],
+            wire_type,
+            lamp_numbers[b],
+            wire_type,
+        ));
+    }
+    for (a, b) in relay_wires {
+        blueprint
+            .wires
+            .push(Wire::new(lamp_numbers[a], 2, lamp_numbers[b], 2));
+    }
+    let palette_signal = |color: usize| {
+        SignalId::new(
+            LAMP_PALETTE[color].signal.unwrap_or_default(),
+            Some("virtual"),
+            None,
+        )
+    };
+    for feed in feeds {
+        let entity_number = blueprint.entities.len() as u64 + 1;
+        let mut filters = vec![signal_filter(1, palette_signal(feed.color), 1)];
+        for (index, color) in feed.suppressed.iter().enumerate() {
+            filters.push(signal_filter(index as u32 + 2, palette_signal(*color), -1));
+        }
+        let (x, y) = feed.position;
+        blueprint.entities.push(constant_combinator(
+            entity_number,
+            Position::new(x as f64, y as f64),
+            vec![LogisticSection {
+                index: 1,
+                filters: filters.into(),
+                ..Default::default()
+            }],
+        ));
+        blueprint.wires.push(Wire::new(
+            entity_number,
+            feed.wire_type,
+            lamp_numbers[feed.target],
+            feed.wire_type,
+        ));
+    }
+    Ok(blueprint)
+}
 
-    let sections = dict["blueprint"]["entities"][0]["control_behavior"]["sections"]["sections"]
-        .as_array_mut()
-        .unwrap();
-    for (index, item_dict) in item_dict_list.iter().enumerate() {
-        sections.push(json!({
-            "filters": item_dict,
-            "index": index + 1
-        }))
+/// 生成超过像素上限的大显示屏
+///
+/// 显示屏按行切分为多块独立面板，每块面板内部连线并复用同一组信号，
+/// 由放在面板左侧的常量运算器单独供给图片数据。未指定信号线时默认使用红线。
+pub fn generate_tiled_display_blueprint<P: AsRef<Path>>(
+    img_path: P,
+    width: u32,
+    height: u32,
+    wire_type_list: Option<Vec<i32>>,
+    always_on: bool,
+    options: &ImageOptions,
+    data: &AppData,
+) -> Result<BluePrint> {
+    let wire_type_list = match wire_type_list {
+        Some(list) if !list.is_empty() => list,
+        _ => vec![1],
+    };
+    let wire_type = wire_type_list[0] as u64;
+
+    let img = image::open(img_path)?;
+    let height = options.output_height(&img, width, height);
+
+    // 调色板模式不占用像素信号，无需切分面板，连线由调色板显示屏自行决定
+    if options.mode == ImageMode::Palette {
+        check_palette_dimensions(width, height)?;
+        let (pixel_list, transparent) = image_rgb_list(&img, width, height, options)?;
+        let mut blueprint =
+            palette_image_blueprint(&pixel_list, &transparent, width, height, always_on, options)?;
+        blueprint.version = Some(data.game_version);
+        return Ok(blueprint);
+    }
+
+    let panels = split_panels(width, height, data.pixel_capacity())?;
+    let (mut pixel_list, transparent) = image_rgb_list(&img, width, height, options)?;
+    apply_palette(&mut pixel_list, width, options);
+
+    let mut blueprint = BluePrint::default();
+    for panel in &panels {
+        let first_lamp = push_screen_panel(
+            &mut blueprint,
+            panel,
+            &wire_type_list,
+            always_on,
+            ImageMode::Rgb,
+            options.alpha == AlphaMode::Off,
+            data,
+        );
+
+        let panel_indices: Vec<usize> = (panel.y..panel.y + panel.height)
+            .flat_map(|y| (panel.x..panel.x + panel.width).map(move |x| (y * width + x) as usize))
+            .collect();
+        let panel_pixels: Vec<[u8; 3]> = panel_indices.iter().map(|i| pixel_list[*i]).collect();
+        let panel_transparent: Vec<bool> = match transparent.is_empty() {
+            true => Vec::new(),
+            false => panel_indices.iter().map(|i| transparent[*i]).collect(),
+        };
+        let entity_number = blueprint.entities.len() as u64 + 1;
+        blueprint.entities.push(constant_combinator(
+            entity_number,
+            Position::new(panel.x as f64 - 1.0, panel.y as f64),
+            image_sections(&panel_pixels, &panel_transparent, data),
+        ));
+        blueprint
+            .wires
+            .push(Wire::new(entity_number, wire_type, first_lamp, wire_type));
+    }
+
+    stamp_signal_hash(&mut blueprint, data);
+    blueprint.version = Some(data.game_version);
+    Ok(blueprint)
+}
+
+/// 生成升级计划，将显示屏中其它品质的电灯升级为指定品质
+pub fn generate_lamp_upgrade_planner(quality: &str, data: &AppData) -> Result<UpgradePlanner> {
+    let qualities = &data.profile.qualities;
+    if !qualities.iter().any(|q| q == quality) {
+        return Err(BluePrintError::InvalidInput(format!("未知的品质：{}", quality)));
+    }
+
+    let mappers = qualities
+        .iter()
+        .filter(|from_quality| *from_quality != quality)
+        .enumerate()
+        .map(|(index, from_quality)| UpgradeMapper {
+            index: index as u32,
+            from: Some(UpgradeTarget::entity("small-lamp", Some(from_quality))),
+            to: Some(UpgradeTarget::entity("small-lamp", Some(quality))),
+            ..Default::default()
+        })
+        .collect();
+
+    let planner = UpgradePlanner {
+        label: Some(format!("显示屏升级（{}）", quality)),
+        settings: Some(UpgradeSettings {
+            icons: vec![Icon {
+                index: 1,
+                signal: SignalId::new("small-lamp", None, Some(quality)),
+            }].into(),
+            mappers,
+            ..Default::default()
+        }),
+        version: Some(data.game_version),
+        ..Default::default()
+    };
+    Ok(planner)
+}
+
+/// 生成只拆除电灯和运算器的拆除计划，用于快速拆除旧显示屏
+pub fn generate_display_deconstruction_planner(data: &AppData) -> Result<DeconstructionPlanner> {
+    let entity_filters = DISPLAY_ENTITY_LIST
+        .iter()
+        .enumerate()
+        .map(|(index, name)| PlannerFilter {
+            index: index as u32 + 1,
+            name: name.to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+    let planner = DeconstructionPlanner {
+        label: Some("拆除显示屏".to_string()),
+        settings: Some(DeconstructionSettings {
+            icons: vec![Icon {
+                index: 1,
+                signal: SignalId::new("small-lamp", None, None),
+            }].into(),
+            entity_filter_mode: Some(FilterMode::WHITELIST.value()),
+            entity_filters,
+            ..Default::default()
+        }),
+        version: Some(data.game_version),
+        ..Default::default()
+    };
+    Ok(planner)
+}
+
+/// 蓝图书中一项的概要
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookChild {
+    pub index: u32,
+    pub kind: &'static str,
+    pub label: Option<String>,
+    /// 子蓝图书中的项数，蓝图为0
+    pub child_count: usize,
+}
+
+/// 将多个蓝图（或蓝图书）字符串按顺序打包为一本蓝图书，其中的蓝图保持原游戏版本
+pub fn generate_blueprint_book(
+    label: Option<String>,
+    blueprint_list: &[String],
+    data: &AppData,
+) -> Result<BlueprintBook> {
+    if blueprint_list.is_empty() {
+        return Err(BluePrintError::InvalidInput("蓝图书至少需要一个蓝图！".to_string()));
+    }
+    let mut records = Vec::new();
+    for blueprint in blueprint_list {
+        records.push(BlueprintRecord::from_blueprint_string(blueprint.trim())?);
     }
 
-    dict_to_blueprint(&dict)
+    let mut book = BlueprintBook::from_records(records);
+    book.label = label;
+    book.version = Some(data.game_version);
+    Ok(book)
+}
+
+/// 沿路径逐层进入子蓝图书，返回路径末端的对象
+fn find_book_record(record: BlueprintRecord, path: &[u32]) -> Result<BlueprintRecord> {
+    let mut current = record;
+    for index in path {
+        let BlueprintRecord::BlueprintBook(mut book) = current else {
+            return Err(BluePrintError::InvalidInput(
+                "路径中包含非蓝图书的对象！".to_string(),
+            ));
+        };
+        let position = book
+            .blueprints
+            .iter()
+            .position(|entry| entry.index == *index)
+            .ok_or_else(|| {
+                BluePrintError::InvalidInput(format!("蓝图书中不存在序号为{}的蓝图！", index))
+            })?;
+        current = book.blueprints.swap_remove(position).record;
+    }
+    Ok(current)
+}
+
+/// 列出蓝图书（或路径指向的子蓝图书）中的所有项
+pub fn list_blueprint_book(blueprint_string: &str, path: &[u32]) -> Result<Vec<BookChild>> {
+    let record = BlueprintRecord::from_blueprint_string(blueprint_string.trim())?;
+    let BlueprintRecord::BlueprintBook(book) = find_book_record(record, path)? else {
+        return Err(BluePrintError::InvalidInput("该蓝图不是蓝图书！".to_string()));
+    };
+
+    let children = book
+        .blueprints
+        .iter()
+        .map(|entry| BookChild {
+            index: entry.index,
+            kind: entry.record.kind(),
+            label: entry.record.label().map(|v| v.to_string()),
+            child_count: match &entry.record {
+                BlueprintRecord::BlueprintBook(child) => child.blueprints.len(),
+                _ => 0,
+            },
+        })
+        .collect();
+    Ok(children)
+}
+
+/// 从蓝图书中取出路径指向的蓝图（或子蓝图书）
+pub fn extract_blueprint_book(blueprint_string: &str, path: &[u32]) -> Result<BlueprintRecord> {
+    let record = BlueprintRecord::from_blueprint_string(blueprint_string.trim())?;
+    find_book_record(record, path)
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs::File;
 
+    use image::imageops::FilterType;
     use image::{ImageBuffer, Rgb};
 
     use super::*;
+    use crate::core::blueprints_circuit::{lamp_color, CircuitNetworks, SignalKey, Signals};
+    use crate::core::blueprints_dict::{matches_pattern, DEFAULT_EXCLUDED_SIGNALS};
+    use crate::core::blueprints_lib::blueprint_to_dict;
 
     #[test]
     fn test_rgb_list() {
@@ -303,6 +1061,293 @@ mod tests {
         
     }
 
+    #[test]
+    fn test_all_signals_blueprint() {
+        let data = AppData::from_signal_dict(serde_json::json!([
+            {"name": "iron-plate"},
+            {"name": "water", "type": "fluid"},
+            {"name": "signal-each", "type": "virtual"},
+            {"name": "signal-A", "type": "virtual"},
+        ]))
+        .unwrap();
+        let options = AllSignalsOptions {
+            qualities: vec!["normal".to_string(), "rare".to_string()],
+            signal_types: vec!["item".to_string(), "virtual".to_string()],
+            section_size: 3,
+            count_mode: CountMode::Sequential,
+            ..Default::default()
+        };
+        let blueprint = generate_all_item_constant_combinator_blueprint(&options, &data).unwrap();
+        let Some(ControlBehavior::ConstantCombinator(behavior)) =
+            &blueprint.entities[0].control_behavior
+        else {
+            panic!("应为常量运算器");
+        };
+        let sections = &behavior.sections.sections;
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].filters.len(), 3);
+        let filters: Vec<_> = sections.iter().flat_map(|s| &s.filters).collect();
+        let names: Vec<_> = filters.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["iron-plate", "iron-plate", "signal-A", "signal-A"]);
+        assert_eq!(filters[3].quality.as_deref(), Some("rare"));
+        assert_eq!(filters.iter().map(|f| f.count).collect::<Vec<_>>(), [1, 2, 3, 4]);
+
+        let options = AllSignalsOptions {
+            section_size: 0,
+            ..Default::default()
+        };
+        assert!(generate_all_item_constant_combinator_blueprint(&options, &data).is_err());
+    }
+
+    #[test]
+    fn test_split_panels() {
+        let data = AppData::embedded().unwrap();
+        let capacity = data.pixel_capacity();
+        // 内置字典去掉默认排除的信号，其余信号各有5种品质
+        let usable = data
+            .signals
+            .iter()
+            .filter(|signal| {
+                !DEFAULT_EXCLUDED_SIGNALS
+                    .iter()
+                    .any(|pattern| matches_pattern(pattern, &signal.name))
+            })
+            .count();
+        assert_eq!(capacity, usable as u32 * 5);
+        let panels = split_panels(200, 150, capacity).unwrap();
+        assert_eq!(panels.len(), 11);
+        assert!(panels.iter().all(|p| p.width * p.height <= capacity));
+        assert_eq!(panels.iter().map(|p| p.height).sum::<u32>(), 150);
+        assert!(split_panels(3000, 1, capacity).is_err());
+    }
+
+    #[test]
+    fn test_tiled_display() {
+        let data = AppData::embedded().unwrap();
+        let img_path = std::env::temp_dir().join("fbp_test_tiled.png");
+        ImageBuffer::from_fn(40, 30, |x, y| Rgb([x as u8, y as u8, 0]))
+            .save(&img_path)
+            .unwrap();
+
+        let blueprint = generate_tiled_display_blueprint(
+            &img_path,
+            200,
+            150,
+            None,
+            true,
+            &ImageOptions::default(),
+            &data,
+        )
+        .unwrap();
+        let combinators: Vec<_> = blueprint
+            .entities
+            .iter()
+            .filter(|e| e.name == "constant-combinator")
+            .collect();
+        assert_eq!(combinators.len(), 11);
+        assert_eq!(blueprint.entities.len(), 200 * 150 + 11);
+        // 实体编号连续且不重复
+        let mut numbers: Vec<u64> = blueprint.entities.iter().map(|e| e.entity_number).collect();
+        numbers.sort();
+        assert!(numbers.iter().enumerate().all(|(i, n)| *n == i as u64 + 1));
+    }
+
+    /// 按电路网络计算调色板显示屏中每个电灯显示的颜色
+    fn palette_lamp_colors(blueprint: &BluePrint) -> HashMap<(i64, i64), Option<[u8; 3]>> {
+        let networks = CircuitNetworks::new(blueprint);
+        blueprint
+            .entities
+            .iter()
+            .filter(|e| e.name == "small-lamp")
+            .map(|e| {
+                let Some(ControlBehavior::Lamp(behavior)) = &e.control_behavior else {
+                    panic!("电灯缺少控制行为");
+                };
+                let color = lamp_color(Some(behavior), &networks.input_signals(e.entity_number));
+                ((e.position.x as i64, e.position.y as i64), color)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_palette_image() {
+        // 全红画面，中央和靠近左边缘各有一个绿点，左上角为黑色，右下角透明
+        let mut pixel_list = vec![[255, 0, 0]; 30 * 30];
+        pixel_list[15 * 30 + 5] = [0, 255, 0];
+        pixel_list[15 * 30 + 15] = [0, 255, 0];
+        pixel_list[0] = [0, 0, 0];
+        let mut transparent = vec![false; 30 * 30];
+        transparent[30 * 30 - 1] = true;
+        let blueprint = palette_image_blueprint(
+            &pixel_list,
+            &transparent,
+            30,
+            30,
+            false,
+            &ImageOptions::default(),
+        )
+        .unwrap();
+
+        // 熄灭和透明像素不放置电灯，其余电灯都显示自己的颜色
+        let colors = palette_lamp_colors(&blueprint);
+        assert_eq!(colors.len(), 30 * 30 - 2);
+        assert!(!colors.contains_key(&(0, 0)) && !colors.contains_key(&(29, 29)));
+        for ((x, y), color) in &colors {
+            let expected = pixel_list[(y * 30 + x) as usize];
+            assert_eq!(*color, Some(expected), "({}, {})", x, y);
+        }
+        assert!(blueprint
+            .entities
+            .iter()
+            .filter(|e| e.name == "small-lamp")
+            .all(|e| e.always_on == Some(false)));
+        // 中央的绿点经由红色电灯用绿线中继到边缘
+        assert!(blueprint.wires.iter().any(|w| w.source_connector == 2));
+        let numbers: HashSet<u64> = blueprint.entities.iter().map(|e| e.entity_number).collect();
+        assert!(blueprint
+            .wires
+            .iter()
+            .all(|w| numbers.contains(&w.source_entity) && numbers.contains(&w.target_entity)));
+
+        // 大面积纯色画面只需要一个运算器
+        let solid = vec![[0, 0, 255]; 200 * 200];
+        let blueprint =
+            palette_image_blueprint(&solid, &[], 200, 200, true, &ImageOptions::default()).unwrap();
+        assert_eq!(blueprint.entities.len(), 200 * 200 + 1);
+        assert_eq!(blueprint.wires.len(), 200 * 200);
+
+        // 远离边缘的多种颜色孤点都能中继，中继电灯仍显示背景色
+        let mut dots = vec![[255, 0, 0]; 60 * 60];
+        let palette: Vec<[u8; 3]> = LAMP_PALETTE[2..].iter().map(|c| c.rgb).collect();
+        for (k, (x, y)) in [12, 24, 36, 48]
+            .into_iter()
+            .flat_map(|x| [12, 24, 36, 48].map(|y| (x, y)))
+            .enumerate()
+        {
+            dots[y * 60 + x] = palette[k % palette.len()];
+        }
+        let blueprint =
+            palette_image_blueprint(&dots, &[], 60, 60, true, &ImageOptions::default()).unwrap();
+        let colors = palette_lamp_colors(&blueprint);
+        assert_eq!(colors.len(), 60 * 60);
+        for ((x, y), color) in &colors {
+            let expected = dots[(y * 60 + x) as usize];
+            assert_eq!(*color, Some(expected), "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn test_palette_screen() {
+        // 调色板模式的显示屏按像素信号开关，颜色取网络中的颜色信号
+        let data = AppData::embedded().unwrap();
+        let screen =
+            generate_screen_blueprint(2, 2, Some(vec![1]), true, ImageMode::Palette, false, &data)
+                .unwrap();
+        assert_eq!(screen.entities.len(), 4);
+        let Some(ControlBehavior::Lamp(behavior)) = &screen.entities[0].control_behavior else {
+            panic!("电灯缺少控制行为");
+        };
+        assert_eq!(behavior.color_mode, Some(0));
+        assert_eq!(behavior.rgb_signal, None);
+
+        let mut signals = Signals::new();
+        signals.insert(SignalKey::new("signal-green", Some("virtual"), None), 1);
+        assert_eq!(lamp_color(Some(behavior), &signals), None);
+        signals.insert(SignalKey::from(&pixel_signal(0, &data)), 3);
+        assert_eq!(lamp_color(Some(behavior), &signals), Some([0, 255, 0]));
+    }
+
+    #[test]
+    fn test_vanilla_screen() {
+        // 原版配置的显示屏不能用到 Space Age 新增的信号和品质
+        let mut data = AppData::embedded().unwrap();
+        data.set_profile("vanilla".parse().unwrap()).unwrap();
+        let capacity = data.pixel_capacity();
+        let screen = generate_screen_blueprint(
+            capacity,
+            1,
+            Some(vec![1]),
+            true,
+            ImageMode::Rgb,
+            false,
+            &data,
+        )
+        .unwrap();
+        let names: Vec<&str> = screen
+            .entities
+            .iter()
+            .filter_map(|entity| match &entity.control_behavior {
+                Some(ControlBehavior::Lamp(behavior)) => behavior.rgb_signal.as_ref(),
+                _ => None,
+            })
+            .inspect(|signal| assert_eq!(signal.quality.as_deref(), Some("normal")))
+            .map(|signal| signal.name.as_str())
+            .collect();
+        assert_eq!(names.len(), capacity as usize);
+        assert!(names.contains(&"iron-plate"));
+        assert!(names.contains(&"crude-oil"));
+        for name in [
+            "tungsten-plate",
+            "agricultural-tower",
+            "ammonia",
+            "metallic-asteroid-chunk",
+            "big-demolisher",
+            "quality-module",
+            "rail-ramp",
+            "turbo-transport-belt",
+        ] {
+            assert!(!names.contains(&name), "原版配置用到了信号{}", name);
+        }
+    }
+
+    #[test]
+    fn test_blueprint_book() {
+        let data = AppData::embedded().unwrap();
+        let labeled = |label: &str| {
+            BlueprintRecord::from(BluePrint {
+                label: Some(label.to_string()),
+                ..Default::default()
+            })
+        };
+        let mut inner = BlueprintBook::from_records(vec![labeled("B"), labeled("C")]);
+        inner.label = Some("内层".to_string());
+        inner.active_index = Some(1);
+        let list = [
+            labeled("A").to_blueprint_string().unwrap(),
+            BlueprintRecord::from(inner).to_blueprint_string().unwrap(),
+        ];
+        let book = generate_blueprint_book(Some("外层".to_string()), &list, &data).unwrap();
+        assert_eq!(book.active_index, Some(0));
+        let book_string = BlueprintRecord::from(book).to_blueprint_string().unwrap();
+
+        let children = list_blueprint_book(&book_string, &[]).unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!((children[1].kind, children[1].child_count), ("blueprint_book", 2));
+        let nested = list_blueprint_book(&book_string, &[1]).unwrap();
+        let labels: Vec<_> = nested.iter().map(|c| c.label.as_deref()).collect();
+        assert_eq!(labels, [Some("B"), Some("C")]);
+
+        // 取出子蓝图书时保留其当前选中的位置
+        let BlueprintRecord::BlueprintBook(extracted) =
+            extract_blueprint_book(&book_string, &[1]).unwrap()
+        else {
+            panic!("应取出蓝图书");
+        };
+        assert_eq!(extracted.active_index, Some(1));
+        let record = extract_blueprint_book(&book_string, &[1, 1]).unwrap();
+        assert_eq!(record.label(), Some("C"));
+
+        // 序号越界、路径穿过蓝图、列出蓝图均报错
+        for path in [&[2][..], &[1, 5], &[0, 0]] {
+            assert!(matches!(
+                extract_blueprint_book(&book_string, path),
+                Err(BluePrintError::InvalidInput(_))
+            ));
+        }
+        assert!(list_blueprint_book(&book_string, &[0]).is_err());
+        assert!(generate_blueprint_book(None, &[], &data).is_err());
+    }
+
     #[test]
     fn test_contrast_image() {
         let path = "C:/Users/hjf/Pictures/icon.png";
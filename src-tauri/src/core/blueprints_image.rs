@@ -0,0 +1,516 @@
+use std::str::FromStr;
+
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+/// 未指定色阶时，抖动默认将每个颜色通道减为8级
+const DEFAULT_DITHER_LEVELS: u8 = 8;
+
+/// 4x4 Bayer 阈值矩阵
+const BAYER_MATRIX: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+/// 抖动算法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DitherMode {
+    /// 不抖动
+    #[default]
+    None,
+    /// Floyd–Steinberg 误差扩散
+    FloydSteinberg,
+    /// Atkinson 误差扩散，只扩散3/4的误差，对比度更高
+    Atkinson,
+    /// 4x4 Bayer 有序抖动
+    Bayer,
+}
+
+impl FromStr for DitherMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(DitherMode::None),
+            "floyd-steinberg" => Ok(DitherMode::FloydSteinberg),
+            "atkinson" => Ok(DitherMode::Atkinson),
+            "bayer" => Ok(DitherMode::Bayer),
+            _ => Err(format!("未知的抖动算法：{}", s)),
+        }
+    }
+}
+
+/// 图片转换模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageMode {
+    /// 颜色打包为 0xRRGGBB，每个像素占用一个信号
+    #[default]
+    Rgb,
+    /// 量化为电灯自带的颜色，由颜色信号驱动，不占用像素信号
+    Palette,
+}
+
+impl FromStr for ImageMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rgb" => Ok(ImageMode::Rgb),
+            "palette" => Ok(ImageMode::Palette),
+            _ => Err(format!("未知的图片模式：{}", s)),
+        }
+    }
+}
+
+/// 图片缩放方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResizeMode {
+    /// 拉伸到指定大小，不保持宽高比
+    #[default]
+    Stretch,
+    /// 完整显示图片，空白处用背景色填充
+    Fit,
+    /// 铺满显示屏，超出部分按焦点裁剪
+    Fill,
+}
+
+impl FromStr for ResizeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stretch" => Ok(ResizeMode::Stretch),
+            "fit" => Ok(ResizeMode::Fit),
+            "fill" => Ok(ResizeMode::Fill),
+            _ => Err(format!("未知的缩放方式：{}", s)),
+        }
+    }
+}
+
+/// 不透明度低于该值的像素视为透明
+const ALPHA_THRESHOLD: u8 = 128;
+
+/// 透明像素的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AlphaMode {
+    /// 丢弃透明度，直接使用像素中保存的颜色
+    #[default]
+    Ignore,
+    /// 与背景色混合
+    Composite,
+    /// 透明像素对应的电灯熄灭
+    Off,
+}
+
+impl FromStr for AlphaMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ignore" => Ok(AlphaMode::Ignore),
+            "composite" => Ok(AlphaMode::Composite),
+            "off" => Ok(AlphaMode::Off),
+            _ => Err(format!("未知的透明处理方式：{}", s)),
+        }
+    }
+}
+
+/// 电灯调色板中的颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LampColor {
+    /// 点亮该颜色的虚拟信号，为空表示电灯熄灭
+    pub signal: Option<&'static str>,
+    pub rgb: [u8; 3],
+}
+
+/// 电灯按颜色信号可显示的颜色，第一项为熄灭
+pub const LAMP_PALETTE: [LampColor; 8] = [
+    LampColor {
+        signal: None,
+        rgb: [0, 0, 0],
+    },
+    LampColor {
+        signal: Some("signal-red"),
+        rgb: [255, 0, 0],
+    },
+    LampColor {
+        signal: Some("signal-green"),
+        rgb: [0, 255, 0],
+    },
+    LampColor {
+        signal: Some("signal-blue"),
+        rgb: [0, 0, 255],
+    },
+    LampColor {
+        signal: Some("signal-yellow"),
+        rgb: [255, 255, 0],
+    },
+    LampColor {
+        signal: Some("signal-pink"),
+        rgb: [255, 0, 255],
+    },
+    LampColor {
+        signal: Some("signal-cyan"),
+        rgb: [0, 255, 255],
+    },
+    LampColor {
+        signal: Some("signal-white"),
+        rgb: [255, 255, 255],
+    },
+];
+
+/// 图片转换选项
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageOptions {
+    #[serde(default)]
+    pub mode: ImageMode,
+    #[serde(default)]
+    pub dither: DitherMode,
+    /// 每个颜色通道保留的色阶数（2~255），为空时不减色
+    #[serde(default)]
+    pub palette_levels: Option<u8>,
+    #[serde(default)]
+    pub resize: ResizeMode,
+    /// Fit 模式留白和透明像素混合使用的背景色
+    #[serde(default)]
+    pub background: [u8; 3],
+    #[serde(default)]
+    pub alpha: AlphaMode,
+    /// Fill 模式下裁剪保留的焦点，按图片宽高的比例（0~1）表示，为空时居中
+    #[serde(default)]
+    pub focal_point: Option<[f32; 2]>,
+    /// 按图片宽高比由宽度计算高度，忽略传入的高度
+    #[serde(default)]
+    pub auto_height: bool,
+}
+
+impl ImageOptions {
+    /// 实际使用的色阶数，不减色也不抖动时为空
+    fn levels(&self) -> Option<u8> {
+        match (self.palette_levels, self.dither) {
+            (Some(levels), _) => Some(levels.max(2)),
+            (None, DitherMode::None) => None,
+            (None, _) => Some(DEFAULT_DITHER_LEVELS),
+        }
+    }
+
+    /// 实际使用的显示屏高度，自动高度时按图片宽高比计算
+    pub fn output_height(&self, image: &DynamicImage, width: u32, height: u32) -> u32 {
+        if !self.auto_height || image.width() == 0 {
+            return height;
+        }
+        let height = width as f64 * image.height() as f64 / image.width() as f64;
+        (height.round() as u32).max(1)
+    }
+}
+
+/// 按缩放方式将图片缩放为指定大小
+pub fn resize_image(
+    image: &DynamicImage,
+    width: u32,
+    height: u32,
+    options: &ImageOptions,
+) -> DynamicImage {
+    match options.resize {
+        ResizeMode::Stretch => image.resize_exact(width, height, FilterType::Lanczos3),
+        ResizeMode::Fit => {
+            let scaled = image.resize(width, height, FilterType::Lanczos3).to_rgba8();
+            let [r, g, b] = options.background;
+            let mut canvas = RgbaImage::from_pixel(width, height, Rgba([r, g, b, 255]));
+            let x = (width - scaled.width()) / 2;
+            let y = (height - scaled.height()) / 2;
+            imageops::overlay(&mut canvas, &scaled, x as i64, y as i64);
+            DynamicImage::ImageRgba8(canvas)
+        }
+        ResizeMode::Fill => {
+            let scale = f64::max(
+                width as f64 / image.width() as f64,
+                height as f64 / image.height() as f64,
+            );
+            let scaled_width = ((image.width() as f64 * scale).round() as u32).max(width);
+            let scaled_height = ((image.height() as f64 * scale).round() as u32).max(height);
+            let scaled = image.resize_exact(scaled_width, scaled_height, FilterType::Lanczos3);
+
+            let [fx, fy] = options.focal_point.unwrap_or([0.5, 0.5]);
+            let crop = |focal: f32, scaled: u32, size: u32| {
+                let start =
+                    (focal.clamp(0.0, 1.0) as f64 * scaled as f64 - size as f64 / 2.0).round();
+                start.clamp(0.0, (scaled - size) as f64) as u32
+            };
+            scaled.crop_imm(
+                crop(fx, scaled_width, width),
+                crop(fy, scaled_height, height),
+                width,
+                height,
+            )
+        }
+    }
+}
+
+/// 按透明处理方式将像素转换为按行排列的rgb列表
+///
+/// Off 模式同时返回每个像素是否透明，其它模式返回空列表。
+pub fn split_alpha(pixels: &RgbaImage, options: &ImageOptions) -> (Vec<[u8; 3]>, Vec<bool>) {
+    let [br, bg, bb] = options.background;
+    let rgb_list = pixels
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b, a] = pixel.0;
+            if options.alpha == AlphaMode::Ignore {
+                return [r, g, b];
+            }
+            let blend = |c: u8, background: u8| {
+                ((c as u32 * a as u32 + background as u32 * (255 - a as u32)) / 255) as u8
+            };
+            [blend(r, br), blend(g, bg), blend(b, bb)]
+        })
+        .collect();
+    let transparent = match options.alpha {
+        AlphaMode::Off => pixels.pixels().map(|p| p.0[3] < ALPHA_THRESHOLD).collect(),
+        _ => Vec::new(),
+    };
+    (rgb_list, transparent)
+}
+
+/// 将单个通道的值量化到最近的色阶
+fn quantize(value: f32, levels: u8) -> f32 {
+    let step = 255.0 / (levels - 1) as f32;
+    ((value / step).round() * step).clamp(0.0, 255.0)
+}
+
+/// 调色板中与颜色最接近的一项
+fn nearest_lamp_color(pixel: [f32; 3]) -> usize {
+    let distance = |color: &LampColor| -> f32 {
+        (0..3)
+            .map(|c| (pixel[c] - color.rgb[c] as f32).powi(2))
+            .sum()
+    };
+    (0..LAMP_PALETTE.len())
+        .min_by(|a, b| distance(&LAMP_PALETTE[*a]).total_cmp(&distance(&LAMP_PALETTE[*b])))
+        .unwrap_or_default()
+}
+
+/// 误差扩散抖动核，(dx, dy, 权重)
+fn dither_kernel(dither: DitherMode) -> &'static [(isize, isize, f32)] {
+    match dither {
+        DitherMode::FloydSteinberg => &[
+            (1, 0, 7.0 / 16.0),
+            (-1, 1, 3.0 / 16.0),
+            (0, 1, 5.0 / 16.0),
+            (1, 1, 1.0 / 16.0),
+        ],
+        DitherMode::Atkinson => &[
+            (1, 0, 1.0 / 8.0),
+            (2, 0, 1.0 / 8.0),
+            (-1, 1, 1.0 / 8.0),
+            (0, 1, 1.0 / 8.0),
+            (1, 1, 1.0 / 8.0),
+            (0, 2, 1.0 / 8.0),
+        ],
+        DitherMode::None | DitherMode::Bayer => &[],
+    }
+}
+
+/// 误差扩散抖动，quantizer 将像素映射为最接近的可显示颜色
+fn diffuse(
+    buffer: &mut [[f32; 3]],
+    width: usize,
+    kernel: &[(isize, isize, f32)],
+    quantizer: impl Fn([f32; 3]) -> [f32; 3],
+) {
+    let height = buffer.len() / width;
+    for y in 0..height {
+        for x in 0..width {
+            let old = buffer[y * width + x];
+            let new = quantizer(old);
+            buffer[y * width + x] = new;
+
+            for (dx, dy, weight) in kernel {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if nx < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                let pixel = &mut buffer[ny as usize * width + nx as usize];
+                for (c, value) in pixel.iter_mut().enumerate() {
+                    *value += (old[c] - new[c]) * weight;
+                }
+            }
+        }
+    }
+}
+
+/// 按选项对像素列表减色和抖动，pixel_list 按行排列
+pub fn apply_palette(pixel_list: &mut [[u8; 3]], width: u32, options: &ImageOptions) {
+    let Some(levels) = options.levels() else {
+        return;
+    };
+    let width = width as usize;
+    if width == 0 {
+        return;
+    }
+
+    let mut buffer: Vec<[f32; 3]> = pixel_list.iter().map(|p| p.map(|v| v as f32)).collect();
+    let quantizer = |pixel: [f32; 3]| pixel.map(|v| quantize(v, levels));
+    match options.dither {
+        DitherMode::None => {
+            for pixel in buffer.iter_mut() {
+                *pixel = quantizer(*pixel);
+            }
+        }
+        DitherMode::FloydSteinberg | DitherMode::Atkinson => {
+            diffuse(&mut buffer, width, dither_kernel(options.dither), quantizer);
+        }
+        DitherMode::Bayer => {
+            let step = 255.0 / (levels - 1) as f32;
+            for (i, pixel) in buffer.iter_mut().enumerate() {
+                let threshold = BAYER_MATRIX[(i / width) % 4][(i % width) % 4] / 16.0 - 0.5;
+                *pixel = pixel.map(|v| quantize(v + threshold * step, levels));
+            }
+        }
+    }
+
+    for (pixel, value) in pixel_list.iter_mut().zip(buffer) {
+        *pixel = value.map(|v| v.round() as u8);
+    }
+}
+
+/// 将像素列表量化为电灯调色板，返回每个像素在 LAMP_PALETTE 中的序号
+pub fn quantize_lamp_palette(pixel_list: &[[u8; 3]], width: u32, dither: DitherMode) -> Vec<usize> {
+    let width = width.max(1) as usize;
+    let mut buffer: Vec<[f32; 3]> = pixel_list.iter().map(|p| p.map(|v| v as f32)).collect();
+    let quantizer = |pixel: [f32; 3]| {
+        LAMP_PALETTE[nearest_lamp_color(pixel)]
+            .rgb
+            .map(|v| v as f32)
+    };
+    match dither {
+        DitherMode::None => {}
+        DitherMode::FloydSteinberg | DitherMode::Atkinson => {
+            diffuse(&mut buffer, width, dither_kernel(dither), quantizer);
+        }
+        DitherMode::Bayer => {
+            for (i, pixel) in buffer.iter_mut().enumerate() {
+                let threshold = BAYER_MATRIX[(i / width) % 4][(i % width) % 4] / 16.0 - 0.5;
+                *pixel = pixel.map(|v| v + threshold * 255.0);
+            }
+        }
+    }
+    buffer.into_iter().map(nearest_lamp_color).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_levels() {
+        let mut pixel_list = vec![[10, 130, 250], [64, 190, 0]];
+        let options = ImageOptions {
+            palette_levels: Some(2),
+            ..Default::default()
+        };
+        apply_palette(&mut pixel_list, 2, &options);
+        assert_eq!(pixel_list, vec![[0, 255, 255], [0, 255, 0]]);
+    }
+
+    #[test]
+    fn test_dither_keeps_average() {
+        // 50%灰用两级色阶抖动后，黑白像素应大致各占一半
+        for dither in [
+            DitherMode::FloydSteinberg,
+            DitherMode::Atkinson,
+            DitherMode::Bayer,
+        ] {
+            let mut pixel_list = vec![[128, 128, 128]; 16 * 16];
+            let options = ImageOptions {
+                dither,
+                palette_levels: Some(2),
+                ..Default::default()
+            };
+            apply_palette(&mut pixel_list, 16, &options);
+            assert!(pixel_list.iter().all(|p| p[0] == 0 || p[0] == 255));
+            let white = pixel_list.iter().filter(|p| p[0] == 255).count();
+            assert!((96..=160).contains(&white), "{:?}: {}", dither, white);
+        }
+    }
+
+    #[test]
+    fn test_resize_modes() {
+        // 4x2 的图片左半红、右半蓝
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(4, 2, |x, _| {
+            if x < 2 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        }));
+
+        let fit = ImageOptions {
+            resize: ResizeMode::Fit,
+            background: [0, 255, 0],
+            ..Default::default()
+        };
+        let fitted = resize_image(&image, 4, 4, &fit).to_rgb8();
+        assert_eq!(fitted.get_pixel(0, 0).0, [0, 255, 0]);
+        assert_eq!(fitted.get_pixel(0, 3).0, [0, 255, 0]);
+
+        let fill = ImageOptions {
+            resize: ResizeMode::Fill,
+            focal_point: Some([0.0, 0.5]),
+            ..Default::default()
+        };
+        let filled = resize_image(&image, 2, 2, &fill).to_rgb8();
+        assert_eq!(filled.dimensions(), (2, 2));
+        assert!(filled.pixels().all(|p| p.0[0] > 200 && p.0[2] < 50));
+
+        let auto = ImageOptions {
+            auto_height: true,
+            ..Default::default()
+        };
+        assert_eq!(auto.output_height(&image, 40, 1), 20);
+    }
+
+    #[test]
+    fn test_split_alpha() {
+        let pixels = RgbaImage::from_vec(2, 1, vec![200, 100, 0, 0, 200, 100, 0, 255]).unwrap();
+        let composite = ImageOptions {
+            alpha: AlphaMode::Composite,
+            background: [255, 255, 255],
+            ..Default::default()
+        };
+        let (rgb_list, transparent) = split_alpha(&pixels, &composite);
+        assert_eq!(rgb_list, vec![[255, 255, 255], [200, 100, 0]]);
+        assert!(transparent.is_empty());
+
+        let off = ImageOptions {
+            alpha: AlphaMode::Off,
+            ..Default::default()
+        };
+        let (_, transparent) = split_alpha(&pixels, &off);
+        assert_eq!(transparent, vec![true, false]);
+    }
+
+    #[test]
+    fn test_quantize_lamp_palette() {
+        let pixel_list = vec![[250, 10, 20], [10, 10, 10], [200, 220, 30], [240, 240, 240]];
+        let indices = quantize_lamp_palette(&pixel_list, 2, DitherMode::None);
+        let signals: Vec<_> = indices.iter().map(|i| LAMP_PALETTE[*i].signal).collect();
+        assert_eq!(
+            signals,
+            vec![
+                Some("signal-red"),
+                None,
+                Some("signal-yellow"),
+                Some("signal-white")
+            ]
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::blueprints_lib::{BluePrint, BlueprintRecord, ControlBehavior, GameVersion, Icon};
+use super::blueprints_validate::{check_blueprint_schema, decode_blueprint_string};
+use super::{BluePrintError, Result};
+
+/// 蓝图占据的范围，以格为单位
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    fn include(&mut self, x0: f64, y0: f64, x1: f64, y1: f64) {
+        self.min_x = self.min_x.min(x0);
+        self.min_y = self.min_y.min(y0);
+        self.max_x = self.max_x.max(x1);
+        self.max_y = self.max_y.max(y1);
+    }
+}
+
+/// 按颜色统计的导线数量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WireCounts {
+    pub red: usize,
+    pub green: usize,
+    /// 电线杆之间的铜线
+    pub copper: usize,
+}
+
+/// 常量运算器中设置的信号统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FilterTotals {
+    pub combinators: usize,
+    pub sections: usize,
+    pub filters: usize,
+}
+
+/// 蓝图概要，蓝图书会累加其中所有蓝图
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlueprintSummary {
+    pub kind: &'static str,
+    pub label: Option<String>,
+    pub icons: Vec<Icon>,
+    /// 导出蓝图的游戏版本
+    pub version: Option<String>,
+    /// 蓝图数量，蓝图书包括子蓝图书中的蓝图
+    pub blueprint_count: usize,
+    pub entity_count: usize,
+    /// 按实体名称统计的数量
+    pub entity_counts: BTreeMap<String, usize>,
+    /// 只有单个蓝图才计算范围
+    pub bounding_box: Option<BoundingBox>,
+    pub wires: WireCounts,
+    pub filters: FilterTotals,
+}
+
+/// 解码后的蓝图
+#[derive(Debug, Clone, Serialize)]
+pub struct BlueprintInspection {
+    /// 格式化的JSON，保留蓝图中的所有字段
+    pub json: String,
+    pub summary: BlueprintSummary,
+    /// 与目标游戏版本不兼容的蓝图
+    pub warnings: Vec<String>,
+}
+
+impl BlueprintSummary {
+    fn new(
+        kind: &'static str,
+        label: Option<&str>,
+        icons: &[Icon],
+        version: Option<GameVersion>,
+    ) -> Self {
+        Self {
+            kind,
+            label: label.map(|v| v.to_string()),
+            icons: icons.to_vec(),
+            version: version.map(|v| v.to_string()),
+            blueprint_count: 0,
+            entity_count: 0,
+            entity_counts: BTreeMap::new(),
+            bounding_box: None,
+            wires: WireCounts::default(),
+            filters: FilterTotals::default(),
+        }
+    }
+
+    /// 累加一个蓝图的统计
+    fn add_blueprint(&mut self, blueprint: &BluePrint) {
+        self.blueprint_count += 1;
+        self.entity_count += blueprint.entities.len();
+        for entity in &blueprint.entities {
+            *self.entity_counts.entry(entity.name.clone()).or_default() += 1;
+            if let Some(ControlBehavior::ConstantCombinator(behavior)) = &entity.control_behavior {
+                self.filters.combinators += 1;
+                self.filters.sections += behavior.sections.sections.len();
+                self.filters.filters += behavior
+                    .sections
+                    .sections
+                    .iter()
+                    .map(|section| section.filters.len())
+                    .sum::<usize>();
+            }
+        }
+        for wire in &blueprint.wires {
+            // 1、3 为红线接点，2、4 为绿线接点，其余为铜线接点
+            match wire.source_connector {
+                1 | 3 => self.wires.red += 1,
+                2 | 4 => self.wires.green += 1,
+                _ => self.wires.copper += 1,
+            }
+        }
+    }
+
+    /// 累加蓝图书中的所有蓝图
+    fn add_record(&mut self, record: &BlueprintRecord) {
+        match record {
+            BlueprintRecord::Blueprint(blueprint) => self.add_blueprint(blueprint),
+            BlueprintRecord::BlueprintBook(book) => {
+                for entry in &book.blueprints {
+                    self.add_record(&entry.record);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 实体和地砖占据的范围，空蓝图返回空
+pub(super) fn bounding_box(blueprint: &BluePrint) -> Option<BoundingBox> {
+    let mut bounds: Option<BoundingBox> = None;
+    let mut include = |x0: f64, y0: f64, x1: f64, y1: f64| match &mut bounds {
+        Some(bounds) => bounds.include(x0, y0, x1, y1),
+        None => {
+            bounds = Some(BoundingBox {
+                min_x: x0,
+                min_y: y0,
+                max_x: x1,
+                max_y: y1,
+            })
+        }
+    };
+    for entity in &blueprint.entities {
+        let (width, height) = entity.size();
+        let (x, y) = (entity.position.x, entity.position.y);
+        include(
+            x - width / 2.0,
+            y - height / 2.0,
+            x + width / 2.0,
+            y + height / 2.0,
+        );
+    }
+    for tile in &blueprint.tiles {
+        let (x, y) = (tile.position.x, tile.position.y);
+        include(x, y, x + 1.0, y + 1.0);
+    }
+    bounds
+}
+
+/// 统计蓝图、蓝图书或规划工具的概要
+pub fn summarize_record(record: &BlueprintRecord) -> BlueprintSummary {
+    let mut summary = match record {
+        BlueprintRecord::Blueprint(blueprint) => {
+            let mut summary = BlueprintSummary::new(
+                record.kind(),
+                blueprint.label.as_deref(),
+                &blueprint.icons,
+                blueprint.version,
+            );
+            summary.bounding_box = bounding_box(blueprint);
+            summary
+        }
+        BlueprintRecord::BlueprintBook(book) => BlueprintSummary::new(
+            record.kind(),
+            book.label.as_deref(),
+            &book.icons,
+            book.version,
+        ),
+        BlueprintRecord::DeconstructionPlanner(planner) => BlueprintSummary::new(
+            record.kind(),
+            planner.label.as_deref(),
+            planner
+                .settings
+                .as_ref()
+                .map_or(&[], |settings| settings.icons.as_slice()),
+            planner.version,
+        ),
+        BlueprintRecord::UpgradePlanner(planner) => BlueprintSummary::new(
+            record.kind(),
+            planner.label.as_deref(),
+            planner
+                .settings
+                .as_ref()
+                .map_or(&[], |settings| settings.icons.as_slice()),
+            planner.version,
+        ),
+    };
+    summary.add_record(record);
+    summary
+}
+
+/// 解码任意蓝图字符串，返回格式化的JSON和概要，并检查是否来自不兼容的游戏版本
+pub fn inspect_blueprint(
+    blueprint_string: &str,
+    target: GameVersion,
+) -> Result<BlueprintInspection> {
+    let dict = decode_blueprint_string(blueprint_string)?;
+    let json =
+        serde_json::to_string_pretty(&dict).map_err(|e| BluePrintError::Codec(e.to_string()))?;
+    let record = check_blueprint_schema(&dict)?;
+    Ok(BlueprintInspection {
+        json,
+        summary: summarize_record(&record),
+        warnings: record.version_warnings(target),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::blueprints_funcs::generate_screen_blueprint;
+    use crate::core::blueprints_image::ImageMode;
+    use crate::core::blueprints_lib::BlueprintBook;
+    use crate::AppData;
+
+    #[test]
+    fn test_inspect_blueprint() {
+        let data = AppData::embedded().unwrap();
+        let screen =
+            generate_screen_blueprint(3, 2, Some(vec![1, 2]), true, ImageMode::Rgb, false, &data)
+                .unwrap();
+        let mut record = BlueprintRecord::from(screen);
+
+        let blueprint_string = record.to_blueprint_string().unwrap();
+        let inspection = inspect_blueprint(&blueprint_string, GameVersion::TARGET).unwrap();
+        assert!(inspection.warnings.is_empty());
+        let summary = &inspection.summary;
+        assert_eq!(summary.kind, "blueprint");
+        assert_eq!(summary.version.as_deref(), Some("2.0.28"));
+        assert_eq!(summary.entity_counts["small-lamp"], 6);
+        assert_eq!(summary.wires.red, summary.wires.green);
+        assert_eq!(summary.wires.copper, 0);
+        let bounds = summary.bounding_box.unwrap();
+        assert_eq!(
+            (bounds.max_x - bounds.min_x, bounds.max_y - bounds.min_y),
+            (3.0, 2.0)
+        );
+        assert!(inspection.json.contains("\"small-lamp\""));
+
+        // 来自 1.1 的蓝图与目标版本不兼容
+        record.set_version(GameVersion::new(1, 1, 110, 0));
+        let old = inspect_blueprint(&record.to_blueprint_string().unwrap(), GameVersion::TARGET);
+        assert_eq!(old.unwrap().warnings.len(), 1);
+
+        // 子蓝图书中的蓝图也会统计
+        let inner = BlueprintBook::from_records(vec![record.clone()]);
+        let book = BlueprintBook::from_records(vec![record, inner.into()]);
+        let summary = summarize_record(&book.into());
+        assert_eq!(summary.kind, "blueprint_book");
+        assert_eq!(summary.blueprint_count, 2);
+        assert_eq!(summary.entity_counts["small-lamp"], 12);
+        assert!(summary.bounding_box.is_none());
+    }
+}
@@ -2,93 +2,747 @@
 
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine};
-use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
-use serde_json::{json, Value};
-use std::io::{Read, Write};
+use flate2::{write::ZlibEncoder, Compression};
+use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+use std::io::Write;
 
-pub fn dict_to_blueprint(blueprint_dict: &Value) -> Result<String> {
-    // 将蓝图数据转换为 JSON 字符串
-    let json_data = serde_json::to_string(blueprint_dict)?;
-    // 压缩 JSON 数据
-    let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
+use super::blueprints_validate::{decode_blueprint_string, validate_blueprint_string};
+use super::BluePrintError;
+
+/// 蓝图字符串的编码参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EncodeOptions {
+    /// zlib 压缩等级 0~9，9 为最佳压缩
+    pub compression_level: u32,
+    /// 省略游戏导入时的默认值，如物品类型、普通品质和等于比较，缩短蓝图字符串
+    pub minify: bool,
+}
+
+impl EncodeOptions {
+    /// 最佳压缩并省略默认值，用于长度受限的论坛和聊天工具
+    pub const SMALLEST: EncodeOptions = EncodeOptions {
+        compression_level: 9,
+        minify: true,
+    };
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            compression_level: Compression::default().level(),
+            minify: false,
+        }
+    }
+}
+
+/// 蓝图字符串各阶段的大小
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeReport {
+    /// JSON 的字节数
+    pub json_bytes: usize,
+    /// zlib 压缩后的字节数
+    pub compressed_bytes: usize,
+    /// 最终蓝图字符串的长度
+    pub string_length: usize,
+}
+
+/// 省略信号和常量运算器筛选器中与默认值相同的字段
+///
+/// 只处理信号ID和 `control_behavior.sections` 中的筛选器。分拣器、请求箱、着陆坪等筛选器缺少品质时表示任意品质，不能省略。
+fn minify_value(value: &mut Value, key: &str, in_sections: bool) {
+    match value {
+        Value::Object(map) => {
+            if key == "signal" || key.ends_with("_signal") || (in_sections && key == "filters") {
+                let defaults = [("type", "item"), ("quality", "normal"), ("comparator", "=")];
+                for (key, default) in defaults {
+                    if map.get(key).and_then(Value::as_str) == Some(default) {
+                        map.remove(key);
+                    }
+                }
+            }
+            for (child_key, child) in map.iter_mut() {
+                let in_sections =
+                    in_sections || (key == "control_behavior" && child_key == "sections");
+                minify_value(child, child_key, in_sections);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                minify_value(item, key, in_sections);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 按参数编码蓝图字符串，同时返回各阶段的大小
+pub fn encode_blueprint(
+    blueprint_dict: &Value,
+    options: &EncodeOptions,
+) -> Result<(String, SizeReport)> {
+    if options.compression_level > 9 {
+        anyhow::bail!("压缩等级应为0~9：{}", options.compression_level);
+    }
+    let json_data = if options.minify {
+        let mut dict = blueprint_dict.clone();
+        minify_value(&mut dict, "", false);
+        serde_json::to_string(&dict)?
+    } else {
+        serde_json::to_string(blueprint_dict)?
+    };
+    let mut e = ZlibEncoder::new(Vec::new(), Compression::new(options.compression_level));
     e.write_all(json_data.as_bytes())?;
     let compressed_data = e.finish()?;
-    // 编码为 Base64
-    let base64_data = general_purpose::STANDARD.encode(&compressed_data);
-    // 添加蓝图前缀
-    let blueprint_string = format!("0{}", base64_data);
+    let blueprint_string = format!("0{}", general_purpose::STANDARD.encode(&compressed_data));
+    let report = SizeReport {
+        json_bytes: json_data.len(),
+        compressed_bytes: compressed_data.len(),
+        string_length: blueprint_string.len(),
+    };
+    Ok((blueprint_string, report))
+}
+
+pub fn dict_to_blueprint(blueprint_dict: &Value) -> Result<String> {
+    // 默认压缩等级，不省略字段
+    let (blueprint_string, _) = encode_blueprint(blueprint_dict, &EncodeOptions::default())?;
     Ok(blueprint_string)
 }
 
+/// 解码蓝图字符串，失败时给出出错的阶段和位置
 pub fn blueprint_to_dict(blueprint_string: &str) -> Result<Value> {
-    let blueprint_string = if blueprint_string.starts_with("0") {
-        &blueprint_string[1..]
+    Ok(decode_blueprint_string(blueprint_string)?)
+}
+
+/// 整数坐标按整数输出，与游戏导出的格式保持一致；输入中写作 `1.0` 的坐标保持小数形式
+fn serialize_coordinate<S: Serializer>(
+    value: f64,
+    decimal: bool,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if !decimal && value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        serializer.serialize_i64(value as i64)
     } else {
-        blueprint_string
-    };
-    // Base64 解码
-    let compressed_data = general_purpose::STANDARD.decode(blueprint_string)?;
-    // 解压缩数据
-    let mut d = ZlibDecoder::new(&compressed_data[..]);
-    let mut json_data = String::new();
-    d.read_to_string(&mut json_data)?;
-    // 反序列化为 JSON
-    let blueprint_data: Value = serde_json::from_str(&json_data)?;
-    Ok(blueprint_data)
+        serializer.serialize_f64(value)
+    }
 }
 
-/// 实体对象
-#[derive(Debug)]
-pub struct Entity {
-    entity_number: Option<i64>,
-    name: Option<String>,
-    entity_type: Option<String>, // type是rust的关键字
-    position: Value,       // {x: 0.0, y: 0.0}
-    direction: Option<i64>,
-    control_behavior: Value,
+/// 蓝图中的数组字段，可以像 `Vec` 一样使用
+///
+/// 记录输入中是否出现该字段，使 `"filters": []` 这样的空数组在重新编码时保持原样；
+/// 没有出现且为空时编码时省略。
+#[derive(Debug, Clone)]
+pub struct List<T> {
+    items: Vec<T>,
+    present: bool,
 }
 
-impl Entity {
-    pub fn new(entity: &Value) -> Self {
+impl<T> List<T> {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            present: false,
+        }
+    }
+
+    /// 输入中没有该字段且没有元素，编码时省略
+    pub fn is_absent(&self) -> bool {
+        !self.present && self.items.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 只比较元素和是否省略，非空数组是否来自输入不影响编码结果
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items && self.is_absent() == other.is_absent()
+    }
+}
+
+impl<T> std::ops::Deref for List<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<T> std::ops::DerefMut for List<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.items
+    }
+}
+
+impl<T> From<Vec<T>> for List<T> {
+    fn from(items: Vec<T>) -> Self {
+        Self {
+            items,
+            present: false,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Vec::from_iter(iter).into()
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter_mut()
+    }
+}
+
+impl<T: Serialize> Serialize for List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.items.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            items: Vec::deserialize(deserializer)?,
+            present: true,
+        })
+    }
+}
+
+/// 游戏版本，蓝图中以64位整数存储，每16位依次为主版本、次版本、补丁和开发版本号
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub struct GameVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+    pub developer: u16,
+}
+
+impl GameVersion {
+    /// 生成蓝图默认使用的游戏版本
+    pub const TARGET: GameVersion = GameVersion::new(2, 0, 28, 0);
+
+    pub const fn new(major: u16, minor: u16, patch: u16, developer: u16) -> Self {
         Self {
-            entity_number: entity["entity_number"].as_i64(),
-            name: entity["name"].as_str().and_then(|v| Some(v.to_string())),
-            entity_type: entity["type"].as_str().and_then(|v| Some(v.to_string())),
-            position: entity["position"].clone(),
-            direction: entity["direction"].as_i64(),
-            control_behavior: entity["control_behavior"].clone(),
+            major,
+            minor,
+            patch,
+            developer,
         }
     }
 
-    /// 获得该实体对象的字典格式
-    pub fn get_dict(&self) -> Value {
-        let mut dict = json!({});
+    /// 蓝图中存储的整数形式
+    pub const fn packed(self) -> u64 {
+        (self.major as u64) << 48
+            | (self.minor as u64) << 32
+            | (self.patch as u64) << 16
+            | self.developer as u64
+    }
 
-        if let Some(entity_number) = self.entity_number {
-            dict["entity_number"] = json!(entity_number);
+    /// 主版本号相同时蓝图可以互相导入
+    pub fn is_compatible(self, other: GameVersion) -> bool {
+        self.major == other.major
+    }
+}
+
+impl From<u64> for GameVersion {
+    fn from(value: u64) -> Self {
+        Self::new(
+            (value >> 48) as u16,
+            (value >> 32) as u16,
+            (value >> 16) as u16,
+            value as u16,
+        )
+    }
+}
+
+impl From<GameVersion> for u64 {
+    fn from(value: GameVersion) -> Self {
+        value.packed()
+    }
+}
+
+impl std::fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl std::str::FromStr for GameVersion {
+    type Err = BluePrintError;
+
+    /// 解析 2.0.28 或 2.0.28.1 格式的版本号
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || BluePrintError::InvalidInput(format!("游戏版本格式应为 2.0.28：{}", s));
+        let parts = s
+            .trim()
+            .split('.')
+            .map(|part| part.parse::<u16>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        match parts[..] {
+            [major, minor, patch] => Ok(Self::new(major, minor, patch, 0)),
+            [major, minor, patch, developer] => Ok(Self::new(major, minor, patch, developer)),
+            _ => Err(invalid()),
         }
-        if let Some(name) = &self.name {
-            dict["name"] = json!(name);
+    }
+}
+
+/// 坐标
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    /// 输入中写作小数形式的整数坐标，重新编码时保持原样
+    decimal: [bool; 2],
+}
+
+impl Position {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self {
+            x,
+            y,
+            decimal: [false; 2],
         }
-        if let Some(type1) = &self.entity_type {
-            dict["type"] = json!(type1);
+    }
+}
+
+/// 只比较坐标值
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+}
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Coordinate(f64, bool);
+        impl Serialize for Coordinate {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serialize_coordinate(self.0, self.1, serializer)
+            }
         }
-        if !self.position.is_null() {
-            dict["position"] = self.position.clone();
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("x", &Coordinate(self.x, self.decimal[0]))?;
+        map.serialize_entry("y", &Coordinate(self.y, self.decimal[1]))?;
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawPosition {
+            x: serde_json::Number,
+            y: serde_json::Number,
         }
-        if let Some(direction) = self.direction {
-            dict["direction"] = json!(direction);
+        let raw = RawPosition::deserialize(deserializer)?;
+        let value = |n: &serde_json::Number| {
+            n.as_f64()
+                .ok_or_else(|| serde::de::Error::custom(format!("坐标不是有效的数字：{}", n)))
+        };
+        Ok(Self {
+            x: value(&raw.x)?,
+            y: value(&raw.y)?,
+            decimal: [raw.x.is_f64(), raw.y.is_f64()],
+        })
+    }
+}
+
+/// 信号ID，type为空时表示物品信号
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SignalId {
+    pub name: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub signal_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+}
+
+impl SignalId {
+    pub fn new(name: &str, signal_type: Option<&str>, quality: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            signal_type: signal_type.map(|v| v.to_string()),
+            quality: quality.map(|v| v.to_string()),
         }
-        if !self.control_behavior.is_null() {
-            dict["control_behavior"] = self.control_behavior.clone();
+    }
+}
+
+/// 蓝图图标
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Icon {
+    pub index: u32,
+    pub signal: SignalId,
+}
+
+/// 导线连接：[实体A, 接口A, 实体B, 接口B]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "[u64; 4]", into = "[u64; 4]")]
+pub struct Wire {
+    pub source_entity: u64,
+    pub source_connector: u64,
+    pub target_entity: u64,
+    pub target_connector: u64,
+}
+
+impl Wire {
+    pub fn new(source_entity: u64, source_connector: u64, target_entity: u64, target_connector: u64) -> Self {
+        Self {
+            source_entity,
+            source_connector,
+            target_entity,
+            target_connector,
         }
+    }
+}
+
+impl From<[u64; 4]> for Wire {
+    fn from(value: [u64; 4]) -> Self {
+        Self::new(value[0], value[1], value[2], value[3])
+    }
+}
+
+impl From<Wire> for [u64; 4] {
+    fn from(value: Wire) -> Self {
+        [
+            value.source_entity,
+            value.source_connector,
+            value.target_entity,
+            value.target_connector,
+        ]
+    }
+}
+
+/// 地砖
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tile {
+    pub name: String,
+    pub position: Position,
+}
+
+/// 电路条件，如电灯的启用条件
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CircuitCondition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constant: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparator: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 常量运算器中的单个信号
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogisticFilter {
+    pub index: u32,
+    pub name: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub signal_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparator: Option<String>,
+    pub count: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_count: Option<i32>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 常量运算器的信号分组
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogisticSection {
+    pub index: u32,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub filters: List<LogisticFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
 
-        dict
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogisticSections {
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub sections: List<LogisticSection>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 常量运算器控制行为
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConstantCombinatorBehavior {
+    pub sections: LogisticSections,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_on: Option<bool>,
+}
+
+/// 算术运算器参数
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArithmeticConditions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_constant: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second_constant: Option<i32>,
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_signal: Option<SignalId>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 算术运算器控制行为
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArithmeticCombinatorBehavior {
+    pub arithmetic_conditions: ArithmeticConditions,
+}
+
+/// 判断运算器读取或输出的信号网络，缺省时红绿两色都使用
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignalNetworks {
+    pub red: bool,
+    pub green: bool,
+}
+
+impl SignalNetworks {
+    pub fn only(wire_type: u64) -> Self {
+        Self {
+            red: wire_type == 1,
+            green: wire_type == 2,
+        }
+    }
+}
+
+/// 判断运算器的单个条件
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeciderCondition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constant: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_signal_networks: Option<SignalNetworks>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second_signal_networks: Option<SignalNetworks>,
+    /// "and" 或 "or"，首个条件没有该字段
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compare_type: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 判断运算器的单个输出
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeciderOutput {
+    pub signal: SignalId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copy_count_from_input: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constant: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub networks: Option<SignalNetworks>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeciderConditions {
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub conditions: List<DeciderCondition>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub outputs: List<DeciderOutput>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 判断运算器控制行为
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeciderCombinatorBehavior {
+    pub decider_conditions: DeciderConditions,
+}
+
+/// 电灯控制行为
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LampBehavior {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_colors: Option<bool>,
+    /// 0: 颜色映射，1: 分量，2: 打包RGB
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_mode: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rgb_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub red_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub green_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blue_signal: Option<SignalId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_condition: Option<CircuitCondition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_to_logistic_network: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logistic_condition: Option<CircuitCondition>,
+}
+
+/// 实体控制行为
+///
+/// 按实体名称选择变体，各变体均拒绝未知字段，无法识别的控制行为原样保存在 `Other` 中
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum ControlBehavior {
+    ConstantCombinator(ConstantCombinatorBehavior),
+    ArithmeticCombinator(ArithmeticCombinatorBehavior),
+    DeciderCombinator(DeciderCombinatorBehavior),
+    Lamp(LampBehavior),
+    Other(Map<String, Value>),
+}
+
+impl ControlBehavior {
+    /// 按实体名称解析控制行为，机械臂、传送带等只有电路条件的实体不会被当作电灯
+    pub fn for_entity(name: &str, behavior: Map<String, Value>) -> Self {
+        let parsed = match name {
+            "constant-combinator" => {
+                ConstantCombinatorBehavior::deserialize(&behavior).map(Self::ConstantCombinator)
+            }
+            "arithmetic-combinator" => {
+                ArithmeticCombinatorBehavior::deserialize(&behavior).map(Self::ArithmeticCombinator)
+            }
+            "decider-combinator" => {
+                DeciderCombinatorBehavior::deserialize(&behavior).map(Self::DeciderCombinator)
+            }
+            "small-lamp" => LampBehavior::deserialize(&behavior).map(Self::Lamp),
+            _ => return Self::Other(behavior),
+        };
+        parsed.unwrap_or(Self::Other(behavior))
+    }
+}
+
+/// 实体对象
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Entity {
+    pub entity_number: u64,
+    pub name: String,
+    pub position: Position,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub entity_type: Option<String>, // type是rust的关键字
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control_behavior: Option<ControlBehavior>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub always_on: Option<bool>,
+    /// 未建模的字段（物品请求、配方、标签等）
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl<'de> Deserialize<'de> for Entity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawEntity {
+            entity_number: u64,
+            name: String,
+            position: Position,
+            #[serde(rename = "type")]
+            entity_type: Option<String>,
+            direction: Option<i64>,
+            quality: Option<String>,
+            control_behavior: Option<Map<String, Value>>,
+            always_on: Option<bool>,
+            #[serde(flatten)]
+            extra: Map<String, Value>,
+        }
+        let raw = RawEntity::deserialize(deserializer)?;
+        let control_behavior = raw
+            .control_behavior
+            .map(|behavior| ControlBehavior::for_entity(&raw.name, behavior));
+        Ok(Self {
+            entity_number: raw.entity_number,
+            name: raw.name,
+            position: raw.position,
+            entity_type: raw.entity_type,
+            direction: raw.direction,
+            quality: raw.quality,
+            control_behavior,
+            always_on: raw.always_on,
+            extra: raw.extra,
+        })
+    }
+}
+
+impl Entity {
+    pub fn new(entity_number: u64, name: &str, position: Position) -> Self {
+        Self {
+            entity_number,
+            name: name.to_string(),
+            position,
+            ..Default::default()
+        }
     }
 
     /// 用新的name或type来置换当前实体
     pub fn replace(&mut self, name: Option<String>, type1: Option<String>) {
-        if name.is_some() {
+        if let Some(name) = name {
             self.name = name;
         }
         if type1.is_some() {
@@ -100,61 +754,954 @@ impl Entity {
     pub fn rotate(&mut self, direction: i64) {
         self.direction = Some(direction);
     }
+
+    /// 实体朝北时占据的格数（宽, 高），未列出的实体按 1x1 处理
+    pub fn size(&self) -> (f64, f64) {
+        let (width, height) = match self.name.as_str() {
+            "arithmetic-combinator" | "decider-combinator" | "selector-combinator" => (1.0, 2.0),
+            "pump" | "offshore-pump" | "loader" | "fast-loader" | "express-loader"
+            | "turbo-loader" => (1.0, 2.0),
+            "splitter" | "fast-splitter" | "express-splitter" | "turbo-splitter" => (2.0, 1.0),
+            "big-electric-pole"
+            | "substation"
+            | "accumulator"
+            | "stone-furnace"
+            | "steel-furnace"
+            | "burner-mining-drill"
+            | "train-stop"
+            | "gun-turret"
+            | "laser-turret"
+            | "lightning-collector" => (2.0, 2.0),
+            "boiler" | "heat-exchanger" => (3.0, 2.0),
+            "flamethrower-turret" | "crusher" => (2.0, 3.0),
+            "recycler" => (2.0, 4.0),
+            "assembling-machine-1"
+            | "assembling-machine-2"
+            | "assembling-machine-3"
+            | "electric-furnace"
+            | "electric-mining-drill"
+            | "chemical-plant"
+            | "centrifuge"
+            | "lab"
+            | "radar"
+            | "beacon"
+            | "storage-tank"
+            | "pumpjack"
+            | "solar-panel"
+            | "biochamber"
+            | "agricultural-tower"
+            | "heating-tower"
+            | "asteroid-collector"
+            | "artillery-turret"
+            | "rocket-turret" => (3.0, 3.0),
+            "steam-engine" | "steam-turbine" | "fusion-generator" => (3.0, 5.0),
+            "roboport" | "electromagnetic-plant" | "cargo-bay" => (4.0, 4.0),
+            "oil-refinery"
+            | "nuclear-reactor"
+            | "foundry"
+            | "cryogenic-plant"
+            | "big-mining-drill"
+            | "railgun-turret"
+            | "captive-biter-spawner" => (5.0, 5.0),
+            "fusion-reactor" => (6.0, 6.0),
+            "cargo-landing-pad" | "space-platform-hub" => (8.0, 8.0),
+            "rocket-silo" => (9.0, 9.0),
+            // 铁轨位于 2x2 的铁轨网格上
+            "straight-rail" | "elevated-straight-rail" | "legacy-straight-rail" => (2.0, 2.0),
+            "half-diagonal-rail" | "elevated-half-diagonal-rail" => (2.0, 4.0),
+            "curved-rail-a"
+            | "elevated-curved-rail-a"
+            | "curved-rail-b"
+            | "elevated-curved-rail-b" => (2.0, 6.0),
+            "legacy-curved-rail" => (4.0, 8.0),
+            "rail-ramp" => (4.0, 16.0),
+            "rail-support" => (4.0, 4.0),
+            _ => (1.0, 1.0),
+        };
+        // 朝东或朝西时宽高互换
+        match self.direction {
+            Some(4) | Some(12) => (height, width),
+            _ => (width, height),
+        }
+    }
+}
+
+/// 时刻表中的等待条件
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WaitCondition {
+    #[serde(rename = "type")]
+    pub condition_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compare_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticks: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<CircuitCondition>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 时刻表中的站点记录
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub station: Option<String>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub wait_conditions: List<WaitCondition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temporary: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 时刻表中的中断
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleInterrupt {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub conditions: List<WaitCondition>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub targets: List<ScheduleRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inside_interrupt: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleData {
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub records: List<ScheduleRecord>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub interrupts: List<ScheduleInterrupt>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 时刻表
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub locomotives: List<u64>,
+    pub schedule: ScheduleData,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 蓝图参数化设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    /// "id" 或 "number"
+    #[serde(rename = "type")]
+    pub parameter_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_parametrised: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// 蓝图对象
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BluePrint {
-    entities: Vec<Entity>,
-    icons: Vec<Value>,
-    label: Option<String>,
-    version: Option<i64>,
+    pub item: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub icons: List<Icon>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub entities: List<Entity>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub tiles: List<Tile>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub wires: List<Wire>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub schedules: List<Schedule>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub parameters: List<Parameter>,
+    #[serde(rename = "snap-to-grid", skip_serializing_if = "Option::is_none")]
+    pub snap_to_grid: Option<Position>,
+    #[serde(rename = "absolute-snapping", skip_serializing_if = "Option::is_none")]
+    pub absolute_snapping: Option<bool>,
+    #[serde(rename = "position-relative-to-grid", skip_serializing_if = "Option::is_none")]
+    pub position_relative_to_grid: Option<Position>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<GameVersion>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for BluePrint {
+    fn default() -> Self {
+        Self {
+            item: "blueprint".to_string(),
+            label: None,
+            description: None,
+            icons: List::new(),
+            entities: List::new(),
+            tiles: List::new(),
+            wires: List::new(),
+            schedules: List::new(),
+            parameters: List::new(),
+            snap_to_grid: None,
+            absolute_snapping: None,
+            position_relative_to_grid: None,
+            version: None,
+            extra: Map::new(),
+        }
+    }
 }
 
 impl BluePrint {
-    pub fn new(blueprint_dict: &Value) -> Self {
-        let blueprint = &blueprint_dict["blueprint"];
+    /// 从 `{"blueprint": {...}}` 格式的字典中解析蓝图
+    pub fn new(blueprint_dict: &Value) -> Result<Self> {
+        let blueprint = blueprint_dict
+            .get("blueprint")
+            .ok_or_else(|| anyhow::anyhow!("缺少blueprint字段"))?;
+        Ok(Self::deserialize(blueprint)?)
+    }
 
-        let mut entities = Vec::new();
-        if let Some(entity_values) = blueprint["entities"].as_array() {
-            for ele in entity_values {
-                entities.push(Entity::new(ele));
-            }
+    /// 从蓝图字符串中解析蓝图
+    pub fn from_blueprint_string(blueprint_string: &str) -> Result<Self> {
+        Self::new(&blueprint_to_dict(blueprint_string)?)
+    }
+
+    /// 获得该蓝图对象的字典形式
+    pub fn get_dict(&self) -> Result<Value> {
+        Ok(json!({"blueprint": serde_json::to_value(self)?}))
+    }
+
+    /// 编码为蓝图字符串
+    pub fn to_blueprint_string(&self) -> Result<String> {
+        dict_to_blueprint(&self.get_dict()?)
+    }
+}
+
+/// 蓝图书中的一项，index 为在书中的位置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookEntry {
+    pub index: u32,
+    #[serde(flatten)]
+    pub record: BlueprintRecord,
+}
+
+/// 蓝图书对象
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlueprintBook {
+    pub item: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub icons: List<Icon>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub blueprints: List<BookEntry>,
+    /// 游戏导出的蓝图书可能没有该字段，缺省时原样保留
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_index: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<GameVersion>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for BlueprintBook {
+    fn default() -> Self {
+        Self {
+            item: "blueprint-book".to_string(),
+            label: None,
+            description: None,
+            icons: List::new(),
+            blueprints: List::new(),
+            active_index: Some(0),
+            version: None,
+            extra: Map::new(),
         }
+    }
+}
 
-        let mut icons = Vec::new();
-        if let Some(ic) = blueprint["icons"].as_array() {
-            icons = ic.clone();
+impl BlueprintBook {
+    /// 依次放入蓝图书，index 从0开始连续编号
+    pub fn from_records(records: Vec<BlueprintRecord>) -> Self {
+        Self {
+            blueprints: records
+                .into_iter()
+                .enumerate()
+                .map(|(index, record)| BookEntry {
+                    index: index as u32,
+                    record,
+                })
+                .collect(),
+            ..Default::default()
         }
+    }
+
+    /// 按 index 查找书中的一项
+    pub fn get(&self, index: u32) -> Option<&BlueprintRecord> {
+        self.blueprints
+            .iter()
+            .find(|entry| entry.index == index)
+            .map(|entry| &entry.record)
+    }
+}
+
+/// 拆除/升级计划中的实体或地砖过滤器
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlannerFilter {
+    pub index: u32,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparator: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 拆除计划设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeconstructionSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub icons: List<Icon>,
+    /// 见 `FilterMode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_filter_mode: Option<u8>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub entity_filters: List<PlannerFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trees_and_rocks_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_filter_mode: Option<u8>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub tile_filters: List<PlannerFilter>,
+    /// 0: 正常，1: 总是，2: 从不，3: 仅地砖
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_selection_mode: Option<u8>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
 
+/// 拆除计划对象
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeconstructionPlanner {
+    pub item: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<DeconstructionSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<GameVersion>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for DeconstructionPlanner {
+    fn default() -> Self {
         Self {
-            entities,
-            icons,
-            label: blueprint["label"]
-                .as_str()
-                .and_then(|v| Some(v.to_string())),
-            version: blueprint["version"].as_i64(),
+            item: "deconstruction-planner".to_string(),
+            label: None,
+            settings: None,
+            version: None,
+            extra: Map::new(),
         }
     }
+}
 
-    /// 获得该蓝图对象的字典形式
-    pub fn get_dict(&self) -> Value {
-        let mut dict = json!({});
+/// 升级计划中替换的源或目标
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeTarget {
+    /// "entity" 或 "item"
+    #[serde(rename = "type")]
+    pub target_type: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparator: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
 
-        if self.entities.len() > 0 {
-            let enetities: Vec<Value> = self.entities.iter().map(|ele| ele.get_dict()).collect();
-            dict["entities"] = json!(enetities);
+impl UpgradeTarget {
+    pub fn entity(name: &str, quality: Option<&str>) -> Self {
+        Self {
+            target_type: "entity".to_string(),
+            name: name.to_string(),
+            quality: quality.map(|v| v.to_string()),
+            ..Default::default()
         }
-        if self.icons.len() > 0 {
-            dict["icons"] = json!(self.icons);
+    }
+}
+
+/// 升级计划中的一条替换规则
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeMapper {
+    pub index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<UpgradeTarget>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<UpgradeTarget>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 升级计划设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub icons: List<Icon>,
+    #[serde(default, skip_serializing_if = "List::is_absent")]
+    pub mappers: List<UpgradeMapper>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 升级计划对象
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpgradePlanner {
+    pub item: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<UpgradeSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<GameVersion>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for UpgradePlanner {
+    fn default() -> Self {
+        Self {
+            item: "upgrade-planner".to_string(),
+            label: None,
+            settings: None,
+            version: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+/// 蓝图字符串的顶层对象
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlueprintRecord {
+    Blueprint(BluePrint),
+    BlueprintBook(BlueprintBook),
+    DeconstructionPlanner(DeconstructionPlanner),
+    UpgradePlanner(UpgradePlanner),
+}
+
+impl BlueprintRecord {
+    /// 从 `{"blueprint": {...}}` 或 `{"blueprint_book": {...}}` 格式的字典中解析
+    pub fn new(blueprint_dict: &Value) -> Result<Self> {
+        Ok(Self::deserialize(blueprint_dict)?)
+    }
+
+    /// 从蓝图字符串中严格解析，失败时给出出错的阶段和位置
+    pub fn from_blueprint_string(blueprint_string: &str) -> Result<Self> {
+        Ok(validate_blueprint_string(blueprint_string)?)
+    }
+
+    /// 获得字典形式
+    pub fn get_dict(&self) -> Result<Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    /// 编码为蓝图字符串
+    pub fn to_blueprint_string(&self) -> Result<String> {
+        dict_to_blueprint(&self.get_dict()?)
+    }
+
+    /// 按参数编码为蓝图字符串，同时返回各阶段的大小
+    pub fn encode(&self, options: &EncodeOptions) -> Result<(String, SizeReport)> {
+        encode_blueprint(&self.get_dict()?, options)
+    }
+
+    /// 顶层对象的键名
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BlueprintRecord::Blueprint(_) => "blueprint",
+            BlueprintRecord::BlueprintBook(_) => "blueprint_book",
+            BlueprintRecord::DeconstructionPlanner(_) => "deconstruction_planner",
+            BlueprintRecord::UpgradePlanner(_) => "upgrade_planner",
+        }
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            BlueprintRecord::Blueprint(bp) => bp.label.as_deref(),
+            BlueprintRecord::BlueprintBook(book) => book.label.as_deref(),
+            BlueprintRecord::DeconstructionPlanner(planner) => planner.label.as_deref(),
+            BlueprintRecord::UpgradePlanner(planner) => planner.label.as_deref(),
+        }
+    }
+
+    /// 导出蓝图的游戏版本
+    pub fn version(&self) -> Option<GameVersion> {
+        match self {
+            BlueprintRecord::Blueprint(bp) => bp.version,
+            BlueprintRecord::BlueprintBook(book) => book.version,
+            BlueprintRecord::DeconstructionPlanner(planner) => planner.version,
+            BlueprintRecord::UpgradePlanner(planner) => planner.version,
         }
-        if let Some(label) = &self.label {
-            dict["label"] = json!(label);
+    }
+
+    /// 设置顶层对象的游戏版本，蓝图书中的蓝图保持原版本
+    pub fn set_version(&mut self, version: GameVersion) {
+        match self {
+            BlueprintRecord::Blueprint(bp) => bp.version = Some(version),
+            BlueprintRecord::BlueprintBook(book) => book.version = Some(version),
+            BlueprintRecord::DeconstructionPlanner(planner) => planner.version = Some(version),
+            BlueprintRecord::UpgradePlanner(planner) => planner.version = Some(version),
         }
-        if let Some(version) = self.version {
-            dict["version"] = json!(version);
+    }
+
+    /// 与目标版本主版本号不同的蓝图，蓝图书会检查其中所有蓝图
+    pub fn version_warnings(&self, target: GameVersion) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let Some(version) = self.version() {
+            if !version.is_compatible(target) {
+                let name = self.label().map_or("蓝图".to_string(), |v| format!("蓝图「{}」", v));
+                warnings.push(format!(
+                    "{}来自游戏版本 {}，与目标版本 {} 不兼容",
+                    name, version, target
+                ));
+            }
+        }
+        if let BlueprintRecord::BlueprintBook(book) = self {
+            for entry in &book.blueprints {
+                warnings.extend(entry.record.version_warnings(target));
+            }
         }
+        warnings
+    }
+}
+
+impl BlueprintRecord {
+    /// 实体总数，蓝图书会累加所有子蓝图
+    pub fn entity_count(&self) -> usize {
+        match self {
+            BlueprintRecord::Blueprint(bp) => bp.entities.len(),
+            BlueprintRecord::BlueprintBook(book) => book
+                .blueprints
+                .iter()
+                .map(|entry| entry.record.entity_count())
+                .sum(),
+            _ => 0,
+        }
+    }
+}
+
+impl From<BluePrint> for BlueprintRecord {
+    fn from(value: BluePrint) -> Self {
+        BlueprintRecord::Blueprint(value)
+    }
+}
+
+impl From<BlueprintBook> for BlueprintRecord {
+    fn from(value: BlueprintBook) -> Self {
+        BlueprintRecord::BlueprintBook(value)
+    }
+}
 
-        json!({"blueprint": dict})
+impl From<DeconstructionPlanner> for BlueprintRecord {
+    fn from(value: DeconstructionPlanner) -> Self {
+        BlueprintRecord::DeconstructionPlanner(value)
+    }
+}
+
+impl From<UpgradePlanner> for BlueprintRecord {
+    fn from(value: UpgradePlanner) -> Self {
+        BlueprintRecord::UpgradePlanner(value)
+    }
+}
+
+/// 从字典中解析单个蓝图，蓝图书和计划返回错误
+pub fn parse_blueprint(dict: &Value) -> Result<BluePrint, BluePrintError> {
+    match BlueprintRecord::new(dict)? {
+        BlueprintRecord::Blueprint(blueprint) => Ok(blueprint),
+        _ => Err(BluePrintError::InvalidInput(
+            "只支持单个蓝图，不支持蓝图书和计划！".to_string(),
+        )),
+    }
+}
+
+/// 返回给前端的蓝图字符串及其基本信息
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlueprintResponse {
+    pub blueprint: String,
+    pub kind: &'static str,
+    pub entity_count: usize,
+    pub string_length: usize,
+    /// JSON、压缩后和最终字符串的大小
+    pub size: SizeReport,
+    /// 蓝图可以生成但可能无法在游戏中正常使用的原因
+    pub warnings: Vec<String>,
+}
+
+impl BlueprintResponse {
+    pub fn new(
+        record: impl Into<BlueprintRecord>,
+        options: &EncodeOptions,
+    ) -> Result<Self, BluePrintError> {
+        let record = record.into();
+        let (blueprint, size) = record.encode(options)?;
+        Ok(Self {
+            kind: record.kind(),
+            entity_count: record.entity_count(),
+            string_length: blueprint.len(),
+            blueprint,
+            size,
+            warnings: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_dict() -> Value {
+        json!({
+            "blueprint": {
+                "item": "blueprint",
+                "label": "屏幕",
+                "description": "测试蓝图",
+                "icons": [{"index": 1, "signal": {"name": "small-lamp"}}],
+                "entities": [
+                    {
+                        "entity_number": 1,
+                        "name": "small-lamp",
+                        "position": {"x": 0.5, "y": 0.5},
+                        "control_behavior": {
+                            "use_colors": true,
+                            "color_mode": 2,
+                            "rgb_signal": {"name": "signal-A", "type": "virtual", "quality": "rare"}
+                        },
+                        "always_on": true
+                    },
+                    {
+                        "entity_number": 2,
+                        "name": "constant-combinator",
+                        "position": {"x": 1, "y": 0},
+                        "direction": 4,
+                        "control_behavior": {
+                            "sections": {
+                                "sections": [{
+                                    "index": 1,
+                                    "filters": [{
+                                        "index": 1,
+                                        "name": "iron-plate",
+                                        "quality": "normal",
+                                        "comparator": "=",
+                                        "count": 16711680
+                                    }]
+                                }]
+                            }
+                        }
+                    },
+                    {
+                        "entity_number": 3,
+                        "name": "arithmetic-combinator",
+                        "position": {"x": 2, "y": 0.5},
+                        "control_behavior": {
+                            "arithmetic_conditions": {
+                                "first_signal": {"name": "signal-each", "type": "virtual"},
+                                "second_constant": 2,
+                                "operation": "*",
+                                "output_signal": {"name": "signal-each", "type": "virtual"}
+                            }
+                        }
+                    },
+                    {
+                        "entity_number": 4,
+                        "name": "decider-combinator",
+                        "position": {"x": 3, "y": 0.5},
+                        "control_behavior": {
+                            "decider_conditions": {
+                                "conditions": [{
+                                    "first_signal": {"name": "signal-T", "type": "virtual"},
+                                    "constant": 10,
+                                    "comparator": "<"
+                                }],
+                                "outputs": [{"signal": {"name": "signal-T", "type": "virtual"}}]
+                            }
+                        }
+                    },
+                    {
+                        "entity_number": 5,
+                        "name": "inserter",
+                        "position": {"x": 4.5, "y": 0.5},
+                        "control_behavior": {"circuit_set_stack_size": true},
+                        "tags": {"foo": "bar"}
+                    }
+                ],
+                "tiles": [{"name": "refined-concrete", "position": {"x": 0, "y": 0}}],
+                "wires": [[1, 1, 2, 1], [3, 3, 4, 1]],
+                "schedules": [{
+                    "locomotives": [6],
+                    "schedule": {
+                        "records": [{
+                            "station": "Iron",
+                            "wait_conditions": [{"type": "time", "compare_type": "or", "ticks": 300}]
+                        }]
+                    }
+                }],
+                "parameters": [{"type": "id", "name": "物品", "id": "parameter-0"}],
+                "snap-to-grid": {"x": 4, "y": 2},
+                "absolute-snapping": true,
+                "version": 562949954928640u64
+            }
+        })
+    }
+
+    #[test]
+    fn test_blueprint_round_trip() {
+        let dict = sample_dict();
+        let blueprint = BluePrint::new(&dict).unwrap();
+        assert_eq!(blueprint.entities.len(), 5);
+        assert_eq!(blueprint.wires[1], Wire::new(3, 3, 4, 1));
+        assert!(matches!(
+            blueprint.entities[0].control_behavior,
+            Some(ControlBehavior::Lamp(_))
+        ));
+        assert!(matches!(
+            blueprint.entities[4].control_behavior,
+            Some(ControlBehavior::Other(_))
+        ));
+        assert_eq!(blueprint.get_dict().unwrap(), dict);
+
+        // 只有电路条件的机械臂和传送带不是电灯
+        for name in ["inserter", "transport-belt", "train-stop"] {
+            let entity: Entity = serde_json::from_value(json!({
+                "entity_number": 1,
+                "name": name,
+                "position": {"x": 0.5, "y": 0.5},
+                "control_behavior": {
+                    "circuit_enabled": true,
+                    "circuit_condition": {"first_signal": {"name": "signal-A", "type": "virtual"}}
+                }
+            }))
+            .unwrap();
+            assert!(matches!(
+                entity.control_behavior,
+                Some(ControlBehavior::Other(_))
+            ));
+        }
+
+        let blueprint_string = dict_to_blueprint(&dict).unwrap();
+        let decoded = BluePrint::from_blueprint_string(&blueprint_string).unwrap();
+        assert_eq!(decoded, blueprint);
+    }
+
+    #[test]
+    fn test_exact_round_trip() {
+        // 键按字母顺序排列，与 serde_json 输出的顺序一致
+        let input = concat!(
+            r#"{"blueprint":{"entities":[{"control_behavior":{"sections":{"sections":"#,
+            r#"[{"filters":[],"index":1}]}},"entity_number":1,"name":"constant-combinator","#,
+            r#""position":{"x":1.0,"y":-0.5}},{"entity_number":2,"name":"small-lamp","#,
+            r#""position":{"x":2,"y":3.0}}],"icons":[],"item":"blueprint","#,
+            r#""tiles":[{"name":"stone-path","position":{"x":-1,"y":0}}],"wires":[]}}"#
+        );
+        let dict: Value = serde_json::from_str(input).unwrap();
+        let record = BlueprintRecord::new(&dict).unwrap();
+        let output = serde_json::to_string(&record.get_dict().unwrap()).unwrap();
+        assert_eq!(output, input);
+
+        // 生成的空数组和整数坐标仍然省略或写作整数
+        let mut blueprint = BluePrint::default();
+        blueprint.entities.push(Entity::new(1, "small-lamp", Position::new(1.0, 0.5)));
+        let output = serde_json::to_string(&blueprint.get_dict().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            concat!(
+                r#"{"blueprint":{"entities":[{"entity_number":1,"name":"small-lamp","#,
+                r#""position":{"x":1,"y":0.5}}],"item":"blueprint"}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_book_round_trip() {
+        let dict = json!({
+            "blueprint_book": {
+                "item": "blueprint-book",
+                "label": "显示屏",
+                "icons": [{"index": 1, "signal": {"name": "small-lamp"}}],
+                "active_index": 1,
+                "blueprints": [
+                    {"index": 0, "blueprint": sample_dict()["blueprint"].clone()},
+                    {
+                        "index": 1,
+                        "blueprint_book": {
+                            "item": "blueprint-book",
+                            "active_index": 0,
+                            "blueprints": [{"index": 0, "blueprint": {"item": "blueprint", "label": "空"}}]
+                        }
+                    }
+                ],
+                "version": 562949954928640u64
+            }
+        });
+        let record = BlueprintRecord::new(&dict).unwrap();
+        let BlueprintRecord::BlueprintBook(book) = &record else {
+            panic!("应解析为蓝图书");
+        };
+        assert_eq!(book.get(0).map(|r| r.kind()), Some("blueprint"));
+        assert_eq!(book.get(1).map(|r| r.kind()), Some("blueprint_book"));
+        assert_eq!(record.get_dict().unwrap(), dict);
+        assert!(BluePrint::new(&dict).is_err());
+
+        // 没有 active_index 的蓝图书原样输出，不补充默认值
+        let text = concat!(
+            r#"{"blueprint_book":{"blueprints":[{"blueprint":{"item":"blueprint"},"index":0}],"#,
+            r#""item":"blueprint-book"}}"#
+        );
+        let record = BlueprintRecord::new(&serde_json::from_str(text).unwrap()).unwrap();
+        let output = serde_json::to_string(&record.get_dict().unwrap()).unwrap();
+        assert_eq!(output, text);
+    }
+
+    #[test]
+    fn test_encode_options() {
+        let dict = json!({
+            "blueprint": {
+                "item": "blueprint",
+                "icons": [{"index": 1, "signal": {"name": "small-lamp", "type": "item"}}],
+                "entities": [
+                    {
+                        "entity_number": 1,
+                        "name": "constant-combinator",
+                        "position": {"x": 0.5, "y": 0.5},
+                        "quality": "normal",
+                        "control_behavior": {"sections": {"sections": [{"index": 1, "filters": [
+                            {"index": 1, "name": "signal-A", "type": "virtual", "quality": "normal", "comparator": "=", "count": 1},
+                            {"index": 2, "name": "signal-B", "type": "virtual", "quality": "rare", "comparator": "=", "count": 2}
+                        ]}]}}
+                    },
+                    {
+                        "entity_number": 2,
+                        "name": "inserter",
+                        "position": {"x": 1.5, "y": 0.5},
+                        "filters": [{"index": 1, "name": "iron-plate", "quality": "normal", "comparator": "="}]
+                    },
+                    {
+                        "entity_number": 3,
+                        "name": "requester-chest",
+                        "position": {"x": 2.5, "y": 0.5},
+                        "request_filters": {"sections": [{"index": 1, "filters": [
+                            {"index": 1, "name": "iron-plate", "quality": "normal", "comparator": "=", "count": 50}
+                        ]}]}
+                    }
+                ]
+            }
+        });
+        let (plain, plain_size) = encode_blueprint(&dict, &EncodeOptions::default()).unwrap();
+        assert_eq!(plain_size.string_length, plain.len());
+        assert_eq!(blueprint_to_dict(&plain).unwrap(), dict);
+
+        let (minified, size) = encode_blueprint(&dict, &EncodeOptions::SMALLEST).unwrap();
+        assert!(size.json_bytes < plain_size.json_bytes);
+        let minified = blueprint_to_dict(&minified).unwrap();
+        let entities = &minified["blueprint"]["entities"];
+        let filters = &entities[0]["control_behavior"]["sections"]["sections"][0]["filters"];
+        assert_eq!(
+            filters[0],
+            json!({"index": 1, "name": "signal-A", "type": "virtual", "count": 1})
+        );
+        assert_eq!(filters[1]["quality"], "rare");
+        assert_eq!(minified["blueprint"]["icons"][0]["signal"], json!({"name": "small-lamp"}));
+        // 实体的品质、分拣器和请求箱的筛选器保持不变
+        assert_eq!(entities[0]["quality"], "normal");
+        assert_eq!(entities[1], dict["blueprint"]["entities"][1]);
+        assert_eq!(entities[2], dict["blueprint"]["entities"][2]);
+        assert_eq!(
+            entities[2]["request_filters"]["sections"][0]["filters"][0]["quality"],
+            "normal"
+        );
+
+        let invalid = EncodeOptions {
+            compression_level: 10,
+            minify: false,
+        };
+        assert!(encode_blueprint(&dict, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_game_version() {
+        let version = GameVersion::from(562949954928640u64);
+        assert_eq!(version, GameVersion::new(2, 0, 23, 0));
+        assert_eq!(version.to_string(), "2.0.23");
+        assert_eq!(version.packed(), 562949954928640);
+        assert_eq!("2.0.28.3".parse::<GameVersion>().unwrap().developer, 3);
+        assert!("2.0".parse::<GameVersion>().is_err());
+        assert!(version.is_compatible(GameVersion::TARGET));
+        assert!(!GameVersion::new(1, 1, 110, 0).is_compatible(GameVersion::TARGET));
+
+        let record = BlueprintRecord::new(&sample_dict()).unwrap();
+        assert_eq!(record.version(), Some(version));
+        assert_eq!(record.version_warnings(GameVersion::new(1, 1, 110, 0)).len(), 1);
+    }
+
+    #[test]
+    fn test_planner_round_trip() {
+        let deconstruction = json!({
+            "deconstruction_planner": {
+                "item": "deconstruction-planner",
+                "label": "拆屏",
+                "settings": {
+                    "entity_filter_mode": 0,
+                    "entity_filters": [
+                        {"index": 1, "name": "small-lamp"},
+                        {"index": 2, "name": "constant-combinator", "quality": "rare", "comparator": "="}
+                    ],
+                    "tile_selection_mode": 3,
+                    "tile_filters": [{"index": 1, "name": "stone-path"}]
+                },
+                "version": 562949954928640u64
+            }
+        });
+        let record = BlueprintRecord::new(&deconstruction).unwrap();
+        assert_eq!(record.kind(), "deconstruction_planner");
+        assert_eq!(record.get_dict().unwrap(), deconstruction);
+
+        let upgrade = json!({
+            "upgrade_planner": {
+                "item": "upgrade-planner",
+                "settings": {
+                    "mappers": [{
+                        "index": 0,
+                        "from": {"type": "entity", "name": "small-lamp"},
+                        "to": {"type": "entity", "name": "small-lamp", "quality": "legendary"}
+                    }],
+                    "icons": [{"index": 1, "signal": {"name": "small-lamp"}}]
+                },
+                "version": 562949954928640u64
+            }
+        });
+        let record = BlueprintRecord::new(&upgrade).unwrap();
+        assert_eq!(record.kind(), "upgrade_planner");
+        assert_eq!(record.get_dict().unwrap(), upgrade);
     }
 }
@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use image::{ImageFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::blueprints_circuit::{
+    blueprint_constant_signals, lamp_color, merge_signals, CircuitNetworks,
+};
+use super::blueprints_lib::{parse_blueprint, ControlBehavior, Entity};
+use super::{BluePrintError, Result};
+
+/// 输出图片单边的最大像素数，超过时自动缩小比例
+const MAX_RENDER_SIZE: u32 = 4096;
+
+/// 图片四周留白的格数
+const MARGIN: f64 = 1.0;
+
+const BACKGROUND: Rgba<u8> = Rgba([24, 24, 24, 255]);
+const LAMP_OFF: Rgba<u8> = Rgba([56, 56, 56, 255]);
+const ENTITY_FILL: Rgba<u8> = Rgba([72, 72, 80, 255]);
+const ENTITY_BORDER: Rgba<u8> = Rgba([150, 150, 160, 255]);
+const LABEL: Rgba<u8> = Rgba([230, 230, 230, 255]);
+const RED_WIRE: Rgba<u8> = Rgba([220, 40, 40, 255]);
+const GREEN_WIRE: Rgba<u8> = Rgba([40, 200, 40, 255]);
+const COPPER_WIRE: Rgba<u8> = Rgba([210, 130, 60, 255]);
+
+/// 3x5 点阵字体，依次为 A~Z、0~9，每行低3位从左到右
+const FONT: [[u8; 5]; 36] = [
+    [0b010, 0b101, 0b111, 0b101, 0b101],
+    [0b110, 0b101, 0b110, 0b101, 0b110],
+    [0b011, 0b100, 0b100, 0b100, 0b011],
+    [0b110, 0b101, 0b101, 0b101, 0b110],
+    [0b111, 0b100, 0b110, 0b100, 0b111],
+    [0b111, 0b100, 0b110, 0b100, 0b100],
+    [0b011, 0b100, 0b101, 0b101, 0b011],
+    [0b101, 0b101, 0b111, 0b101, 0b101],
+    [0b111, 0b010, 0b010, 0b010, 0b111],
+    [0b001, 0b001, 0b001, 0b101, 0b010],
+    [0b101, 0b101, 0b110, 0b101, 0b101],
+    [0b100, 0b100, 0b100, 0b100, 0b111],
+    [0b101, 0b111, 0b111, 0b101, 0b101],
+    [0b110, 0b101, 0b101, 0b101, 0b101],
+    [0b010, 0b101, 0b101, 0b101, 0b010],
+    [0b110, 0b101, 0b110, 0b100, 0b100],
+    [0b010, 0b101, 0b101, 0b110, 0b011],
+    [0b110, 0b101, 0b110, 0b101, 0b101],
+    [0b011, 0b100, 0b010, 0b001, 0b110],
+    [0b111, 0b010, 0b010, 0b010, 0b010],
+    [0b101, 0b101, 0b101, 0b101, 0b111],
+    [0b101, 0b101, 0b101, 0b101, 0b010],
+    [0b101, 0b101, 0b111, 0b111, 0b101],
+    [0b101, 0b101, 0b010, 0b101, 0b101],
+    [0b101, 0b101, 0b010, 0b010, 0b010],
+    [0b111, 0b001, 0b010, 0b100, 0b111],
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b110, 0b001, 0b010, 0b100, 0b111],
+    [0b110, 0b001, 0b010, 0b001, 0b110],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b110, 0b001, 0b110],
+    [0b011, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b110],
+];
+
+/// 预览图选项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderOptions {
+    /// 每格的像素数
+    #[serde(default = "default_scale")]
+    pub scale: u32,
+    /// 绘制信号线
+    #[serde(default = "default_show_wires")]
+    pub show_wires: bool,
+}
+
+fn default_scale() -> u32 {
+    8
+}
+
+fn default_show_wires() -> bool {
+    true
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            scale: default_scale(),
+            show_wires: default_show_wires(),
+        }
+    }
+}
+
+/// 连线在实体上的端点，运算器的输入端在背面、输出端在正面
+fn connector_point(entity: &Entity, connector: u64) -> (f64, f64) {
+    let (x, y) = (entity.position.x, entity.position.y);
+    let is_combinator = matches!(
+        entity.name.as_str(),
+        "arithmetic-combinator" | "decider-combinator" | "selector-combinator"
+    );
+    if !is_combinator {
+        return (x, y);
+    }
+    let offset = if connector >= 3 { -0.5 } else { 0.5 };
+    match entity.direction.unwrap_or_default() {
+        4 => (x - offset, y),
+        8 => (x, y - offset),
+        12 => (x + offset, y),
+        _ => (x, y + offset),
+    }
+}
+
+/// 实体名称各段的首字母，最多3个
+fn entity_label(name: &str) -> String {
+    name.split(['-', '_'])
+        .filter_map(|part| part.chars().next())
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .take(3)
+        .collect()
+}
+
+fn fill_rect(canvas: &mut RgbaImage, x0: i64, y0: i64, x1: i64, y1: i64, color: Rgba<u8>) {
+    let (width, height) = (canvas.width() as i64, canvas.height() as i64);
+    for y in y0.max(0)..y1.min(height) {
+        for x in x0.max(0)..x1.min(width) {
+            canvas.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
+
+fn draw_line(canvas: &mut RgbaImage, from: (i64, i64), to: (i64, i64), color: Rgba<u8>) {
+    let (mut x, mut y) = from;
+    let (dx, dy) = ((to.0 - x).abs(), -(to.1 - y).abs());
+    let (sx, sy) = ((to.0 - x).signum(), (to.1 - y).signum());
+    let mut error = dx + dy;
+    loop {
+        fill_rect(canvas, x, y, x + 1, y + 1, color);
+        if (x, y) == to {
+            break;
+        }
+        let e2 = 2 * error;
+        if e2 >= dy {
+            error += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            error += dx;
+            y += sy;
+        }
+    }
+}
+
+/// 在矩形中央绘制文字，放不下时不绘制
+fn draw_label(canvas: &mut RgbaImage, text: &str, x0: i64, y0: i64, x1: i64, y1: i64) {
+    let glyphs: Vec<&[u8; 5]> = text
+        .chars()
+        .filter_map(|c| match c {
+            'A'..='Z' => Some(&FONT[c as usize - 'A' as usize]),
+            '0'..='9' => Some(&FONT[c as usize - '0' as usize + 26]),
+            _ => None,
+        })
+        .collect();
+    if glyphs.is_empty() {
+        return;
+    }
+    let text_width = glyphs.len() as i64 * 4 - 1;
+    let size = ((x1 - x0 - 2) / text_width).min((y1 - y0 - 2) / 5);
+    if size <= 0 {
+        return;
+    }
+    let left = x0 + (x1 - x0 - text_width * size) / 2;
+    let top = y0 + (y1 - y0 - 5 * size) / 2;
+    for (index, glyph) in glyphs.iter().enumerate() {
+        let glyph_left = left + index as i64 * 4 * size;
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    let x = glyph_left + column * size;
+                    let y = top + row as i64 * size;
+                    fill_rect(canvas, x, y, x + size, y + size, LABEL);
+                }
+            }
+        }
+    }
+}
+
+/// 绘制蓝图的俯视预览图
+///
+/// 电灯按其连接的常量运算器输出的信号着色，paired 为单独生成的图片蓝图时，
+/// 其中的常量运算器信号会叠加到每个电灯上。其它实体绘制为带名称缩写的方框。
+pub fn render_blueprint(
+    dict: &Value,
+    paired: Option<&Value>,
+    options: &RenderOptions,
+) -> Result<RgbaImage> {
+    let blueprint = parse_blueprint(dict)?;
+    if blueprint.entities.is_empty() {
+        return Err(BluePrintError::InvalidInput("蓝图中没有实体！".to_string()));
+    }
+    let paired_signals = match paired {
+        Some(paired) => blueprint_constant_signals(&parse_blueprint(paired)?),
+        None => Default::default(),
+    };
+
+    let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+    let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+    for entity in &blueprint.entities {
+        let (width, height) = entity.size();
+        min_x = min_x.min(entity.position.x - width / 2.0);
+        min_y = min_y.min(entity.position.y - height / 2.0);
+        max_x = max_x.max(entity.position.x + width / 2.0);
+        max_y = max_y.max(entity.position.y + height / 2.0);
+    }
+    min_x -= MARGIN;
+    min_y -= MARGIN;
+    let tiles_x = (max_x - min_x + MARGIN).ceil() as u32;
+    let tiles_y = (max_y - min_y + MARGIN).ceil() as u32;
+    if tiles_x.max(tiles_y) > MAX_RENDER_SIZE {
+        return Err(BluePrintError::InvalidInput(format!(
+            "蓝图尺寸{}x{}过大，无法预览！",
+            tiles_x, tiles_y
+        )));
+    }
+    let scale = options
+        .scale
+        .min(MAX_RENDER_SIZE / tiles_x.max(tiles_y))
+        .max(1);
+    let mut canvas = RgbaImage::from_pixel(tiles_x * scale, tiles_y * scale, BACKGROUND);
+    let scale = scale as i64;
+    let to_pixel = |x: f64, y: f64| {
+        (
+            ((x - min_x) * scale as f64).round() as i64,
+            ((y - min_y) * scale as f64).round() as i64,
+        )
+    };
+    // 比例足够大时各格之间留出1像素的间隙
+    let gap = if scale >= 4 { 1 } else { 0 };
+
+    let networks = CircuitNetworks::new(&blueprint);
+    for entity in &blueprint.entities {
+        let (width, height) = entity.size();
+        let (x0, y0) = to_pixel(
+            entity.position.x - width / 2.0,
+            entity.position.y - height / 2.0,
+        );
+        let (x1, y1) = to_pixel(
+            entity.position.x + width / 2.0,
+            entity.position.y + height / 2.0,
+        );
+        let (x1, y1) = (x1 - gap, y1 - gap);
+
+        if entity.name == "small-lamp" {
+            let mut signals = networks.input_signals(entity.entity_number);
+            merge_signals(&mut signals, &paired_signals);
+            let behavior = match &entity.control_behavior {
+                Some(ControlBehavior::Lamp(behavior)) => Some(behavior),
+                _ => None,
+            };
+            let color = lamp_color(behavior, &signals)
+                .map(|[r, g, b]| Rgba([r, g, b, 255]))
+                .unwrap_or(LAMP_OFF);
+            fill_rect(&mut canvas, x0, y0, x1, y1, color);
+            continue;
+        }
+
+        fill_rect(&mut canvas, x0, y0, x1, y1, ENTITY_BORDER);
+        fill_rect(&mut canvas, x0 + 1, y0 + 1, x1 - 1, y1 - 1, ENTITY_FILL);
+        draw_label(&mut canvas, &entity_label(&entity.name), x0, y0, x1, y1);
+    }
+
+    if options.show_wires {
+        let entities: HashMap<u64, &Entity> = blueprint
+            .entities
+            .iter()
+            .map(|entity| (entity.entity_number, entity))
+            .collect();
+        for wire in &blueprint.wires {
+            let (Some(source), Some(target)) = (
+                entities.get(&wire.source_entity),
+                entities.get(&wire.target_entity),
+            ) else {
+                continue;
+            };
+            // 红线和绿线错开绘制，避免重叠
+            let (color, offset) = match wire.source_connector {
+                1 | 3 => (RED_WIRE, -1),
+                2 | 4 => (GREEN_WIRE, 1),
+                _ => (COPPER_WIRE, 0),
+            };
+            let (x0, y0) = connector_point(source, wire.source_connector);
+            let (x1, y1) = connector_point(target, wire.target_connector);
+            let (x0, y0) = to_pixel(x0, y0);
+            let (x1, y1) = to_pixel(x1, y1);
+            let offset = if scale >= 4 { offset } else { 0 };
+            draw_line(
+                &mut canvas,
+                (x0 + offset, y0 + offset),
+                (x1 + offset, y1 + offset),
+                color,
+            );
+        }
+    }
+
+    Ok(canvas)
+}
+
+/// 绘制蓝图预览图并编码为PNG
+pub fn render_blueprint_png(
+    dict: &Value,
+    paired: Option<&Value>,
+    options: &RenderOptions,
+) -> Result<Vec<u8>> {
+    encode_png(&render_blueprint(dict, paired, options)?)
+}
+
+/// 将图片编码为PNG
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::blueprints_funcs::{constant_combinator, generate_screen_blueprint};
+    use crate::core::blueprints_image::ImageMode;
+    use crate::core::blueprints_lib::{LogisticFilter, LogisticSection, Position, Wire};
+    use crate::AppData;
+
+    #[test]
+    fn test_render_lamp_color() {
+        let data = AppData::embedded().unwrap();
+        let mut blueprint =
+            generate_screen_blueprint(2, 1, Some(vec![1]), true, ImageMode::Rgb, false, &data)
+                .unwrap();
+        let signal = data.signals[0].name.clone();
+        let signal_type = data.signals[0].signal_type.clone();
+        let combinator = blueprint.entities.len() as u64 + 1;
+        blueprint.entities.push(constant_combinator(
+            combinator,
+            Position::new(0.0, 2.0),
+            vec![LogisticSection {
+                index: 1,
+                filters: vec![LogisticFilter {
+                    index: 1,
+                    name: signal,
+                    signal_type,
+                    comparator: Some("=".to_string()),
+                    count: 0x3366cc,
+                    ..Default::default()
+                }]
+                .into(),
+                ..Default::default()
+            }],
+        ));
+        blueprint.wires.push(Wire::new(combinator, 1, 1, 1));
+
+        let options = RenderOptions {
+            scale: 8,
+            show_wires: false,
+        };
+        let canvas = render_blueprint(&blueprint.get_dict().unwrap(), None, &options).unwrap();
+        // 第一个电灯位于 (0, 0)，左上角留白1格，中心为 (12, 12)
+        assert_eq!(canvas.get_pixel(12, 12), &Rgba([0x33, 0x66, 0xcc, 255]));
+        let png = render_blueprint_png(&blueprint.get_dict().unwrap(), None, &options).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}
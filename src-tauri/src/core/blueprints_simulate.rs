@@ -0,0 +1,268 @@
+use std::collections::{BTreeMap, HashSet};
+
+use image::{Rgba, RgbaImage};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::blueprints_circuit::{blueprint_constant_signals, lamp_color, SignalKey};
+use super::blueprints_dict::blueprint_signal_hash;
+use super::blueprints_lib::{parse_blueprint, ControlBehavior, LampBehavior};
+use super::{BluePrintError, Result};
+
+/// 模拟结果图片单边的最大像素数，与预览图的上限相同
+const MAX_SCREEN_SIZE: u32 = 4096;
+
+/// 模拟显示屏时发现的问题
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SimulationIssue {
+    /// 电灯读取的信号在常量运算器中不存在
+    MissingSignal {
+        x: u32,
+        y: u32,
+        signal: String,
+        quality: String,
+    },
+    /// 常量运算器中同一信号和品质出现多次，游戏中数值会相加
+    DuplicateFilter {
+        signal: String,
+        quality: String,
+        count: usize,
+    },
+    /// 多个电灯读取同一信号和品质
+    DuplicateLamp {
+        signal: String,
+        quality: String,
+        count: usize,
+    },
+    /// 电灯数量与常量运算器的信号数量不一致
+    SizeMismatch { lamps: usize, signals: usize },
+    /// 两个蓝图描述中记录的信号字典哈希不同，生成时使用了不同的字典或配置
+    SignalHashMismatch { screen: String, image: String },
+}
+
+/// 显示屏模拟结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationReport {
+    pub width: u32,
+    pub height: u32,
+    pub issues: Vec<SimulationIssue>,
+    /// 还原出的画面，熄灭和缺少信号的电灯为透明像素
+    #[serde(skip)]
+    pub image: RgbaImage,
+}
+
+/// 电灯按颜色打包模式读取的信号
+fn rgb_signal(behavior: Option<&LampBehavior>) -> Option<SignalKey> {
+    let behavior = behavior?;
+    if behavior.use_colors != Some(true) || behavior.color_mode != Some(2) {
+        return None;
+    }
+    behavior.rgb_signal.as_ref().map(SignalKey::from)
+}
+
+/// 按信号统计出现次数，返回出现多于一次的信号
+fn duplicates(keys: impl Iterator<Item = SignalKey>) -> Vec<(SignalKey, usize)> {
+    let mut counts: BTreeMap<SignalKey, usize> = BTreeMap::new();
+    for key in keys {
+        *counts.entry(key).or_default() += 1;
+    }
+    counts.into_iter().filter(|(_, count)| *count > 1).collect()
+}
+
+/// 模拟图片蓝图接到显示屏后显示的画面
+///
+/// image 为 `generate_image_blueprint` 生成的常量运算器蓝图，screen 为
+/// `generate_screen_blueprint` 生成的显示屏。每个电灯按 rgb_signal 的名称和品质
+/// 在运算器中查找数值，并报告缺少的信号、重复的信号和尺寸不一致。
+pub fn simulate_screen(screen: &Value, image: &Value) -> Result<SimulationReport> {
+    let screen = parse_blueprint(screen)?;
+    let image = parse_blueprint(image)?;
+    let lamps: Vec<_> = screen
+        .entities
+        .iter()
+        .filter(|entity| entity.name == "small-lamp")
+        .map(|entity| {
+            let behavior = match &entity.control_behavior {
+                Some(ControlBehavior::Lamp(behavior)) => Some(behavior),
+                _ => None,
+            };
+            let x = entity.position.x.floor() as i64;
+            let y = entity.position.y.floor() as i64;
+            (x, y, behavior)
+        })
+        .collect();
+    if lamps.is_empty() {
+        return Err(BluePrintError::InvalidInput(
+            "显示屏蓝图中没有电灯！".to_string(),
+        ));
+    }
+
+    let mut issues = Vec::new();
+    if let (Some(screen_hash), Some(image_hash)) = (
+        blueprint_signal_hash(&screen),
+        blueprint_signal_hash(&image),
+    ) {
+        if screen_hash != image_hash {
+            issues.push(SimulationIssue::SignalHashMismatch {
+                screen: screen_hash.to_string(),
+                image: image_hash.to_string(),
+            });
+        }
+    }
+    let filters = image
+        .entities
+        .iter()
+        .filter_map(|entity| match &entity.control_behavior {
+            Some(ControlBehavior::ConstantCombinator(behavior)) => Some(behavior),
+            _ => None,
+        })
+        .flat_map(|behavior| &behavior.sections.sections)
+        .flat_map(|section| &section.filters);
+    for (key, count) in duplicates(filters.map(SignalKey::from)) {
+        issues.push(SimulationIssue::DuplicateFilter {
+            signal: key.name,
+            quality: key.quality,
+            count,
+        });
+    }
+    let lamp_signals = lamps
+        .iter()
+        .filter_map(|(_, _, behavior)| rgb_signal(*behavior));
+    for (key, count) in duplicates(lamp_signals) {
+        issues.push(SimulationIssue::DuplicateLamp {
+            signal: key.name,
+            quality: key.quality,
+            count,
+        });
+    }
+
+    let signals = blueprint_constant_signals(&image);
+    let rgb_lamps = lamps
+        .iter()
+        .filter(|(_, _, behavior)| rgb_signal(*behavior).is_some())
+        .count();
+    if rgb_lamps > 0 && rgb_lamps != signals.len() {
+        issues.push(SimulationIssue::SizeMismatch {
+            lamps: rgb_lamps,
+            signals: signals.len(),
+        });
+    }
+
+    let min_x = lamps.iter().map(|(x, _, _)| *x).min().unwrap_or_default();
+    let min_y = lamps.iter().map(|(_, y, _)| *y).min().unwrap_or_default();
+    let max_x = lamps.iter().map(|(x, _, _)| *x).max().unwrap_or_default();
+    let max_y = lamps.iter().map(|(_, y, _)| *y).max().unwrap_or_default();
+    let width = max_x.saturating_sub(min_x).saturating_add(1);
+    let height = max_y.saturating_sub(min_y).saturating_add(1);
+    if width.max(height) > i64::from(MAX_SCREEN_SIZE) {
+        return Err(BluePrintError::InvalidInput(format!(
+            "显示屏尺寸{}x{}过大，无法模拟！",
+            width, height
+        )));
+    }
+    let (width, height) = (width as u32, height as u32);
+    let mut canvas = RgbaImage::new(width, height);
+    // 同一位置只取第一个电灯
+    let mut placed = HashSet::new();
+    for (x, y, behavior) in &lamps {
+        let (x, y) = ((x - min_x) as u32, (y - min_y) as u32);
+        if !placed.insert((x, y)) {
+            continue;
+        }
+        if let Some(key) = rgb_signal(*behavior) {
+            if !signals.contains_key(&key) {
+                issues.push(SimulationIssue::MissingSignal {
+                    x,
+                    y,
+                    signal: key.name,
+                    quality: key.quality,
+                });
+                continue;
+            }
+        }
+        if let Some([r, g, b]) = lamp_color(*behavior, &signals) {
+            canvas.put_pixel(x, y, Rgba([r, g, b, 255]));
+        }
+    }
+
+    Ok(SimulationReport {
+        width,
+        height,
+        issues,
+        image: canvas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageBuffer, Rgb};
+
+    use super::*;
+    use crate::core::blueprints_funcs::{generate_image_blueprint, generate_screen_blueprint};
+    use crate::core::blueprints_image::{ImageMode, ImageOptions};
+    use crate::AppData;
+
+    #[test]
+    fn test_simulate_screen() {
+        let data = AppData::embedded().unwrap();
+        let img_path = std::env::temp_dir().join("fbp_test_simulate.png");
+        ImageBuffer::from_fn(4, 3, |x, y| Rgb([x as u8 * 60, y as u8 * 80, 10]))
+            .save(&img_path)
+            .unwrap();
+        let screen = generate_screen_blueprint(4, 3, None, true, ImageMode::Rgb, false, &data)
+            .unwrap()
+            .get_dict()
+            .unwrap();
+        let image =
+            generate_image_blueprint(&img_path, 4, 3, true, &ImageOptions::default(), &data)
+                .unwrap();
+
+        let report = simulate_screen(&screen, &image.get_dict().unwrap()).unwrap();
+        assert!(report.issues.is_empty());
+        assert_eq!((report.width, report.height), (4, 3));
+        assert_eq!(report.image.get_pixel(3, 2), &Rgba([180, 160, 10, 255]));
+
+        // 去掉最后一个信号并重复第一个信号
+        let mut drifted = image.clone();
+        drifted.description = Some("信号字典：0000000000000000".to_string());
+        let Some(ControlBehavior::ConstantCombinator(behavior)) =
+            &mut drifted.entities[0].control_behavior
+        else {
+            panic!("图片蓝图应为常量运算器");
+        };
+        let filters = &mut behavior.sections.sections[0].filters;
+        let first = filters[0].clone();
+        *filters.last_mut().unwrap() = first;
+        let report = simulate_screen(&screen, &drifted.get_dict().unwrap()).unwrap();
+        assert!(report
+            .issues
+            .iter()
+            .any(|issue| matches!(issue, SimulationIssue::DuplicateFilter { count: 2, .. })));
+        assert!(report
+            .issues
+            .iter()
+            .any(|issue| matches!(issue, SimulationIssue::MissingSignal { .. })));
+        assert!(report
+            .issues
+            .iter()
+            .any(|issue| matches!(issue, SimulationIssue::SignalHashMismatch { .. })));
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            SimulationIssue::SizeMismatch {
+                lamps: 12,
+                signals: 11
+            }
+        )));
+
+        // 电灯分布过散时拒绝生成画布
+        let mut scattered = parse_blueprint(&screen).unwrap();
+        scattered.entities[0].position.x = 1e12;
+        let scattered = scattered.get_dict().unwrap();
+        assert!(matches!(
+            simulate_screen(&scattered, &image.get_dict().unwrap()),
+            Err(BluePrintError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,497 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::blueprints_consts::DirectionType;
+use super::blueprints_inspect::bounding_box;
+use super::blueprints_lib::{BluePrint, Entity, Position};
+
+/// 顺时针旋转的角度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub enum Rotation {
+    Clockwise90,
+    Clockwise180,
+    Clockwise270,
+}
+
+impl Rotation {
+    /// 旋转的直角个数
+    pub fn quarter_turns(self) -> u32 {
+        match self {
+            Rotation::Clockwise90 => 1,
+            Rotation::Clockwise180 => 2,
+            Rotation::Clockwise270 => 3,
+        }
+    }
+}
+
+impl TryFrom<u32> for Rotation {
+    type Error = String;
+
+    fn try_from(degrees: u32) -> Result<Self, Self::Error> {
+        match degrees {
+            90 => Ok(Rotation::Clockwise90),
+            180 => Ok(Rotation::Clockwise180),
+            270 => Ok(Rotation::Clockwise270),
+            _ => Err(format!("旋转角度应为 90、180 或 270：{}", degrees)),
+        }
+    }
+}
+
+impl From<Rotation> for u32 {
+    fn from(value: Rotation) -> Self {
+        value.quarter_turns() * 90
+    }
+}
+
+impl FromStr for Rotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let degrees = s
+            .parse::<u32>()
+            .map_err(|_| format!("旋转角度应为 90、180 或 270：{}", s))?;
+        Self::try_from(degrees)
+    }
+}
+
+/// 镜像方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FlipAxis {
+    /// 左右镜像
+    Horizontal,
+    /// 上下镜像
+    Vertical,
+}
+
+impl FromStr for FlipAxis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(FlipAxis::Horizontal),
+            "vertical" => Ok(FlipAxis::Vertical),
+            _ => Err(format!("未知的镜像方向：{}", s)),
+        }
+    }
+}
+
+/// 对蓝图的一次几何变换
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Transform {
+    /// 平移整数格，保持实体与网格对齐
+    Translate {
+        dx: i32,
+        dy: i32,
+    },
+    Rotate {
+        rotation: Rotation,
+    },
+    Flip {
+        axis: FlipAxis,
+    },
+}
+
+/// 没有方向的实体，其余实体缺少方向字段时视为朝北，变换时一并旋转
+///
+/// 车辆和列车使用 `orientation` 表示朝向，也不设置方向字段。
+fn is_undirected(name: &str) -> bool {
+    matches!(
+        name,
+        "small-lamp"
+            | "small-electric-pole"
+            | "medium-electric-pole"
+            | "big-electric-pole"
+            | "substation"
+            | "wooden-chest"
+            | "iron-chest"
+            | "steel-chest"
+            | "active-provider-chest"
+            | "passive-provider-chest"
+            | "storage-chest"
+            | "buffer-chest"
+            | "requester-chest"
+            | "infinity-chest"
+            | "linked-chest"
+            | "pipe"
+            | "infinity-pipe"
+            | "heat-pipe"
+            | "heat-interface"
+            | "stone-wall"
+            | "land-mine"
+            | "stone-furnace"
+            | "steel-furnace"
+            | "electric-furnace"
+            | "lab"
+            | "biolab"
+            | "beacon"
+            | "radar"
+            | "roboport"
+            | "accumulator"
+            | "solar-panel"
+            | "lightning-rod"
+            | "lightning-collector"
+            | "power-switch"
+            | "programmable-speaker"
+            | "electric-energy-interface"
+            | "nuclear-reactor"
+            | "heating-tower"
+            | "rocket-silo"
+            | "cargo-landing-pad"
+            | "cargo-bay"
+            | "space-platform-hub"
+            | "agricultural-tower"
+            | "car"
+            | "tank"
+            | "spidertron"
+            | "locomotive"
+            | "cargo-wagon"
+            | "fluid-wagon"
+            | "artillery-wagon"
+    )
+}
+
+/// 铁轨，坐标必须落在 2x2 的铁轨网格上
+fn is_rail(name: &str) -> bool {
+    matches!(
+        name,
+        "straight-rail"
+            | "half-diagonal-rail"
+            | "curved-rail-a"
+            | "curved-rail-b"
+            | "elevated-straight-rail"
+            | "elevated-half-diagonal-rail"
+            | "elevated-curved-rail-a"
+            | "elevated-curved-rail-b"
+            | "legacy-straight-rail"
+            | "legacy-curved-rail"
+            | "rail-ramp"
+            | "rail-support"
+    )
+}
+
+/// 镜像时需要切换 `mirror` 标记的非对称实体
+fn is_mirrorable(name: &str) -> bool {
+    matches!(
+        name,
+        "assembling-machine-1"
+            | "assembling-machine-2"
+            | "assembling-machine-3"
+            | "oil-refinery"
+            | "chemical-plant"
+            | "foundry"
+            | "electromagnetic-plant"
+            | "cryogenic-plant"
+            | "biochamber"
+            | "recycler"
+            | "crusher"
+            | "fusion-reactor"
+            | "fusion-generator"
+    )
+}
+
+/// 变换实体方向，游戏导出时省略朝北的方向
+fn transform_direction(
+    entity: &mut Entity,
+    eight_way: impl Fn(DirectionType) -> DirectionType,
+    sixteen_way: impl Fn(i64) -> i64,
+) {
+    if entity.direction.is_none() && is_undirected(&entity.name) {
+        return;
+    }
+    let direction = entity.direction.unwrap_or_default();
+    let direction = match DirectionType::from_value(direction) {
+        Some(direction) => eight_way(direction).value(),
+        None => sixteen_way(direction).rem_euclid(16),
+    };
+    entity.direction = (direction != 0).then_some(direction);
+}
+
+/// 变换车辆和列车的朝向，朝向为 0~1 的小数，0 为北，顺时针增大
+fn transform_orientation(entity: &mut Entity, transform: impl Fn(f64) -> f64) {
+    if let Some(orientation) = entity.extra.get("orientation").and_then(Value::as_f64) {
+        let orientation = transform(orientation).rem_euclid(1.0);
+        entity
+            .extra
+            .insert("orientation".to_string(), json!(orientation));
+    }
+}
+
+/// 切换实体的镜像标记，游戏导出时省略未镜像的标记
+fn toggle_mirror(entity: &mut Entity) {
+    if !is_mirrorable(&entity.name) {
+        return;
+    }
+    let mirror = entity.extra.get("mirror").and_then(Value::as_bool) == Some(true);
+    if mirror {
+        entity.extra.remove("mirror");
+    } else {
+        entity.extra.insert("mirror".to_string(), Value::Bool(true));
+    }
+}
+
+/// 交换分流器左右两侧的输入、输出优先级
+fn swap_priorities(entity: &mut Entity) {
+    for key in ["input_priority", "output_priority"] {
+        let swapped = match entity.extra.get(key).and_then(Value::as_str) {
+            Some("left") => "right",
+            Some("right") => "left",
+            _ => continue,
+        };
+        entity.extra.insert(key.to_string(), json!(swapped));
+    }
+}
+
+impl BluePrint {
+    /// 平移实体和地砖
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        let (dx, dy) = (dx as f64, dy as f64);
+        for entity in &mut self.entities {
+            entity.position = Position::new(entity.position.x + dx, entity.position.y + dy);
+        }
+        for tile in &mut self.tiles {
+            tile.position = Position::new(tile.position.x + dx, tile.position.y + dy);
+        }
+    }
+
+    /// 顺时针旋转，实体方向随之旋转，旋转后左上角保持在原位置
+    pub fn rotate(&mut self, rotation: Rotation) {
+        let turns = rotation.quarter_turns();
+        self.keep_anchor(|blueprint| {
+            for _ in 0..turns {
+                // 实体坐标为中心点，地砖坐标为左上角，旋转后地砖左上角变为右上角
+                for entity in &mut blueprint.entities {
+                    let Position { x, y, .. } = entity.position;
+                    entity.position = Position::new(-y, x);
+                }
+                for tile in &mut blueprint.tiles {
+                    let Position { x, y, .. } = tile.position;
+                    tile.position = Position::new(-y - 1.0, x);
+                }
+            }
+            for entity in &mut blueprint.entities {
+                transform_direction(entity, |d| d.rotate(turns), |d| d + 4 * turns as i64);
+                transform_orientation(entity, |o| o + 0.25 * turns as f64);
+            }
+            if turns % 2 == 1 {
+                if let Some(grid) = &mut blueprint.snap_to_grid {
+                    *grid = Position::new(grid.y, grid.x);
+                }
+            }
+        });
+    }
+
+    /// 镜像，实体方向随之镜像，镜像后左上角保持在原位置
+    pub fn flip(&mut self, axis: FlipAxis) {
+        self.keep_anchor(|blueprint| {
+            for entity in &mut blueprint.entities {
+                let Position { x, y, .. } = entity.position;
+                match axis {
+                    FlipAxis::Horizontal => {
+                        entity.position = Position::new(-x, y);
+                        transform_direction(entity, |d| d.flip_horizontal(), |d| 16 - d);
+                        transform_orientation(entity, |o| 1.0 - o);
+                    }
+                    FlipAxis::Vertical => {
+                        entity.position = Position::new(x, -y);
+                        transform_direction(entity, |d| d.flip_vertical(), |d| 8 - d);
+                        transform_orientation(entity, |o| 0.5 - o);
+                    }
+                }
+                toggle_mirror(entity);
+                swap_priorities(entity);
+            }
+            for tile in &mut blueprint.tiles {
+                let Position { x, y, .. } = tile.position;
+                tile.position = match axis {
+                    FlipAxis::Horizontal => Position::new(-x - 1.0, y),
+                    FlipAxis::Vertical => Position::new(x, -y - 1.0),
+                };
+            }
+        });
+    }
+
+    /// 依次执行多个变换
+    pub fn transform(&mut self, transforms: &[Transform]) {
+        for transform in transforms {
+            match *transform {
+                Transform::Translate { dx, dy } => self.translate(dx, dy),
+                Transform::Rotate { rotation } => self.rotate(rotation),
+                Transform::Flip { axis } => self.flip(axis),
+            }
+        }
+    }
+
+    /// 执行变换后按实体占地平移整数格，使左上角回到原位置
+    ///
+    /// 含有铁轨时只平移偶数格，使铁轨仍然落在铁轨网格上。
+    fn keep_anchor(&mut self, transform: impl FnOnce(&mut BluePrint)) {
+        let before = bounding_box(self);
+        let step = if self.entities.iter().any(|e| is_rail(&e.name)) {
+            2.0
+        } else {
+            1.0
+        };
+        transform(self);
+        if let (Some(before), Some(after)) = (before, bounding_box(self)) {
+            let dx = ((before.min_x - after.min_x) / step).round() * step;
+            let dy = ((before.min_y - after.min_y) / step).round() * step;
+            self.translate(dx as i32, dy as i32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::blueprints_lib::Tile;
+
+    fn sample_blueprint() -> BluePrint {
+        BluePrint {
+            entities: vec![
+                Entity::new(1, "arithmetic-combinator", Position::new(0.5, 1.0)),
+                Entity::new(2, "small-lamp", Position::new(2.5, 0.5)),
+            ]
+            .into(),
+            tiles: vec![Tile {
+                name: "stone-path".to_string(),
+                position: Position::new(2.0, 1.0),
+            }]
+            .into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_direction_type() {
+        assert_eq!(DirectionType::NORTH.rotate(1), DirectionType::EAST);
+        assert_eq!(DirectionType::WEST.rotate(3), DirectionType::SOUTH);
+        assert_eq!(DirectionType::EAST.flip_horizontal(), DirectionType::WEST);
+        assert_eq!(
+            DirectionType::NORTH_EAST.flip_vertical(),
+            DirectionType::SOUTH_EAST
+        );
+        assert_eq!(DirectionType::from_value(3), None);
+    }
+
+    #[test]
+    fn test_transform_blueprint() {
+        // 1x2 的运算器占据 (0,0)-(1,2)，旋转后占据 (0,0)-(2,1)
+        let mut blueprint = sample_blueprint();
+        blueprint.rotate(Rotation::Clockwise90);
+        let combinator = &blueprint.entities[0];
+        assert_eq!(combinator.position, Position::new(1.0, 0.5));
+        assert_eq!(combinator.direction, Some(4));
+        assert_eq!(blueprint.entities[1].position, Position::new(1.5, 2.5));
+        assert_eq!(blueprint.entities[1].direction, None);
+        assert_eq!(blueprint.tiles[0].position, Position::new(0.0, 2.0));
+        let bounds = bounding_box(&blueprint).unwrap();
+        assert_eq!((bounds.min_x, bounds.min_y), (0.0, 0.0));
+        assert_eq!((bounds.max_x, bounds.max_y), (2.0, 3.0));
+
+        // 旋转四次回到原样
+        let original = sample_blueprint();
+        let mut rotated = original.clone();
+        rotated.transform(&[
+            Transform::Rotate {
+                rotation: Rotation::Clockwise180,
+            },
+            Transform::Rotate {
+                rotation: Rotation::Clockwise90,
+            },
+            Transform::Rotate {
+                rotation: Rotation::Clockwise90,
+            },
+        ]);
+        assert_eq!(rotated, original);
+
+        let mut flipped = original.clone();
+        flipped.flip(FlipAxis::Horizontal);
+        assert_eq!(flipped.entities[0].position, Position::new(2.5, 1.0));
+        assert_eq!(flipped.entities[1].position, Position::new(0.5, 0.5));
+        assert_eq!(flipped.tiles[0].position, Position::new(0.0, 1.0));
+        flipped.flip(FlipAxis::Vertical);
+        assert_eq!(flipped.entities[0].direction, Some(8));
+        flipped.translate(-1, 2);
+        assert_eq!(flipped.entities[0].position, Position::new(1.5, 3.0));
+
+        assert_eq!("270".parse::<Rotation>(), Ok(Rotation::Clockwise270));
+        assert!("45".parse::<Rotation>().is_err());
+    }
+
+    #[test]
+    fn test_transform_asymmetric_entities() {
+        let mut car = Entity::new(3, "car", Position::new(7.0, 1.0));
+        car.extra.insert("orientation".to_string(), json!(0.125));
+        let mut splitter = Entity::new(4, "splitter", Position::new(9.0, 0.5));
+        splitter
+            .extra
+            .insert("input_priority".to_string(), json!("left"));
+        splitter
+            .extra
+            .insert("output_priority".to_string(), json!("right"));
+        let mut blueprint = BluePrint {
+            entities: vec![
+                Entity::new(1, "oil-refinery", Position::new(2.5, 2.5)),
+                Entity::new(2, "assembling-machine-1", Position::new(6.5, 3.5)),
+                car,
+                splitter,
+                Entity::new(5, "my-mod-machine", Position::new(11.5, 0.5)),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        // 左右镜像只切换镜像标记，上下镜像还会使朝北变为朝南
+        blueprint.flip(FlipAxis::Horizontal);
+        let refinery = &blueprint.entities[0];
+        assert_eq!(refinery.direction, None);
+        assert_eq!(refinery.extra["mirror"], json!(true));
+        assert_eq!(blueprint.entities[2].extra["orientation"], json!(0.875));
+        // 镜像后分流器左右两侧的优先级互换
+        let splitter = &blueprint.entities[3];
+        assert_eq!(splitter.extra["input_priority"], json!("right"));
+        assert_eq!(splitter.extra["output_priority"], json!("left"));
+        blueprint.flip(FlipAxis::Vertical);
+        let refinery = &blueprint.entities[0];
+        assert_eq!(refinery.direction, Some(8));
+        assert!(!refinery.extra.contains_key("mirror"));
+        assert_eq!(blueprint.entities[2].extra["orientation"], json!(0.625));
+        let splitter = &blueprint.entities[3];
+        assert_eq!(splitter.direction, Some(8));
+        assert_eq!(splitter.extra["input_priority"], json!("left"));
+        assert_eq!(splitter.extra["output_priority"], json!("right"));
+
+        // 未列出的实体默认有方向，车辆仍然只有朝向
+        blueprint.rotate(Rotation::Clockwise90);
+        assert_eq!(blueprint.entities[0].direction, Some(12));
+        assert_eq!(blueprint.entities[1].direction, Some(12));
+        assert_eq!(blueprint.entities[2].direction, None);
+        assert_eq!(blueprint.entities[2].extra["orientation"], json!(0.875));
+        assert_eq!(blueprint.entities[4].direction, Some(12));
+        // 5x5 的炼油厂仍然从左上角开始
+        let bounds = bounding_box(&blueprint).unwrap();
+        assert_eq!((bounds.min_x, bounds.min_y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_transform_rails() {
+        let mut blueprint = BluePrint {
+            entities: vec![
+                Entity::new(1, "straight-rail", Position::new(1.0, 1.0)),
+                Entity::new(2, "inserter", Position::new(-0.5, 0.5)),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        // 左上角需要平移一格，为保持铁轨网格平移两格
+        blueprint.rotate(Rotation::Clockwise90);
+        assert_eq!(blueprint.entities[0].position, Position::new(1.0, 3.0));
+        assert_eq!(blueprint.entities[0].direction, Some(4));
+        assert_eq!(blueprint.entities[1].position, Position::new(1.5, 1.5));
+        assert_eq!(blueprint.entities[1].direction, Some(4));
+    }
+}
@@ -0,0 +1,495 @@
+use std::fmt::Display;
+use std::io::Read;
+
+use base64::{engine::general_purpose, Engine};
+use flate2::read::ZlibDecoder;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use serde_path_to_error::Segment;
+
+use super::blueprints_lib::BlueprintRecord;
+
+/// 蓝图字符串校验失败的阶段和位置，偏移量为原字符串中的字节位置
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "stage", rename_all = "camelCase")]
+pub enum ValidationError {
+    /// 去掉空白后为空
+    Empty,
+    /// 第一个字符不是版本号 0
+    VersionPrefix { offset: usize, found: char },
+    /// 出现 base64 字母表以外的字符
+    Base64Alphabet { offset: usize, found: char },
+    /// base64 长度不是4的倍数，或 `=` 出现在末尾以外的位置
+    Base64Padding { offset: usize },
+    /// zlib 数据损坏或被截断
+    Zlib { message: String },
+    /// JSON 语法错误，行列从1开始
+    Json {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// 字段缺失或类型错误，path 如 `$.blueprint.entities[3].position.x`
+    Schema { path: String, message: String },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::Empty => write!(f, "蓝图字符串为空"),
+            ValidationError::VersionPrefix { offset, found } => {
+                write!(f, "偏移 {} 处的版本号应为 0，实际为 {:?}", offset, found)
+            }
+            ValidationError::Base64Alphabet { offset, found } => {
+                write!(f, "偏移 {} 处的 {:?} 不是 base64 字符", offset, found)
+            }
+            ValidationError::Base64Padding { offset } => {
+                write!(f, "偏移 {} 处的 base64 填充不正确，蓝图可能不完整", offset)
+            }
+            ValidationError::Zlib { message } => write!(f, "zlib 解压失败：{}", message),
+            ValidationError::Json {
+                line,
+                column,
+                message,
+            } => write!(f, "JSON 第{}行第{}列有误：{}", line, column, message),
+            ValidationError::Schema { path, message } => write!(f, "{}：{}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+type Result<T, E = ValidationError> = std::result::Result<T, E>;
+
+fn is_base64_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'+' || byte == b'/'
+}
+
+/// 解码蓝图字符串，依次检查版本号、base64、zlib 和 JSON 语法
+///
+/// 首尾及中间的空白和换行会被忽略，剪贴板粘贴的内容常带有这些字符。
+pub fn decode_blueprint_string(blueprint_string: &str) -> Result<Value> {
+    // 保留每个字节在原字符串中的位置，用于报告偏移量
+    let bytes: Vec<(usize, u8)> = blueprint_string
+        .bytes()
+        .enumerate()
+        .filter(|(_, byte)| !byte.is_ascii_whitespace())
+        .collect();
+    let Some(&(offset, prefix)) = bytes.first() else {
+        return Err(ValidationError::Empty);
+    };
+    if prefix != b'0' {
+        let found = blueprint_string[offset..]
+            .chars()
+            .next()
+            .unwrap_or_default();
+        return Err(ValidationError::VersionPrefix { offset, found });
+    }
+
+    let payload = &bytes[1..];
+    let padding_start =
+        payload.len() - payload.iter().rev().take_while(|(_, b)| *b == b'=').count();
+    for (index, &(offset, byte)) in payload.iter().enumerate() {
+        if byte == b'=' {
+            if index < padding_start {
+                return Err(ValidationError::Base64Padding { offset });
+            }
+        } else if !is_base64_char(byte) {
+            let found = blueprint_string[offset..]
+                .chars()
+                .next()
+                .unwrap_or_default();
+            return Err(ValidationError::Base64Alphabet { offset, found });
+        }
+    }
+    let end = blueprint_string.trim_end().len();
+    if payload.is_empty() || !payload.len().is_multiple_of(4) || payload.len() - padding_start > 2 {
+        return Err(ValidationError::Base64Padding { offset: end });
+    }
+    let encoded: Vec<u8> = payload.iter().map(|(_, byte)| *byte).collect();
+    let compressed = general_purpose::STANDARD
+        .decode(&encoded)
+        .map_err(|_| ValidationError::Base64Padding { offset: end })?;
+
+    let mut json_data = Vec::new();
+    ZlibDecoder::new(&compressed[..])
+        .read_to_end(&mut json_data)
+        .map_err(|e| ValidationError::Zlib {
+            message: e.to_string(),
+        })?;
+    serde_json::from_slice(&json_data).map_err(|e| ValidationError::Json {
+        line: e.line(),
+        column: e.column(),
+        message: e.to_string(),
+    })
+}
+
+fn schema_error(path: &str, message: impl Into<String>) -> ValidationError {
+    ValidationError::Schema {
+        path: path.to_string(),
+        message: message.into(),
+    }
+}
+
+fn expect_object<'a>(value: &'a Value, path: &str) -> Result<&'a Map<String, Value>> {
+    value
+        .as_object()
+        .ok_or_else(|| schema_error(path, "应为对象"))
+}
+
+/// 读取对象中的字段及其路径，字段为 null 时视为不存在
+fn field<'a>(
+    object: &'a Map<String, Value>,
+    path: &str,
+    key: &str,
+    required: bool,
+) -> Result<Option<(&'a Value, String)>> {
+    let path = format!("{}.{}", path, key);
+    match object.get(key) {
+        Some(Value::Null) | None if required => Err(schema_error(&path, "缺少该字段")),
+        Some(Value::Null) | None => Ok(None),
+        Some(value) => Ok(Some((value, path))),
+    }
+}
+
+/// 读取必需的字段
+fn required<'a>(
+    object: &'a Map<String, Value>,
+    path: &str,
+    key: &str,
+) -> Result<(&'a Value, String)> {
+    field(object, path, key, true).map(|value| value.expect("必需的字段已检查"))
+}
+
+fn check_string(object: &Map<String, Value>, path: &str, key: &str, required: bool) -> Result<()> {
+    if let Some((value, path)) = field(object, path, key, required)? {
+        if !value.is_string() {
+            return Err(schema_error(&path, "应为字符串"));
+        }
+    }
+    Ok(())
+}
+
+fn check_number(object: &Map<String, Value>, path: &str, key: &str, required: bool) -> Result<()> {
+    if let Some((value, path)) = field(object, path, key, required)? {
+        if !value.is_number() {
+            return Err(schema_error(&path, "应为数字"));
+        }
+    }
+    Ok(())
+}
+
+fn check_index(object: &Map<String, Value>, path: &str, key: &str, required: bool) -> Result<()> {
+    if let Some((value, path)) = field(object, path, key, required)? {
+        if !value.is_u64() {
+            return Err(schema_error(&path, "应为非负整数"));
+        }
+    }
+    Ok(())
+}
+
+/// 依次检查数组中的每一项
+fn check_array(
+    object: &Map<String, Value>,
+    path: &str,
+    key: &str,
+    check: impl Fn(&Value, &str) -> Result<()>,
+) -> Result<()> {
+    if let Some((value, path)) = field(object, path, key, false)? {
+        let items = value
+            .as_array()
+            .ok_or_else(|| schema_error(&path, "应为数组"))?;
+        for (index, item) in items.iter().enumerate() {
+            check(item, &format!("{}[{}]", path, index))?;
+        }
+    }
+    Ok(())
+}
+
+fn check_position(value: &Value, path: &str) -> Result<()> {
+    let object = expect_object(value, path)?;
+    check_number(object, path, "x", true)?;
+    check_number(object, path, "y", true)
+}
+
+fn check_icon(value: &Value, path: &str) -> Result<()> {
+    let object = expect_object(value, path)?;
+    check_index(object, path, "index", true)?;
+    let (signal, path) = required(object, path, "signal")?;
+    check_string(expect_object(signal, &path)?, &path, "name", true)
+}
+
+fn check_entity(value: &Value, path: &str) -> Result<()> {
+    let object = expect_object(value, path)?;
+    check_index(object, path, "entity_number", true)?;
+    check_string(object, path, "name", true)?;
+    let (position, position_path) = required(object, path, "position")?;
+    check_position(position, &position_path)?;
+    check_number(object, path, "direction", false)?;
+    check_string(object, path, "quality", false)?;
+    if let Some((behavior, path)) = field(object, path, "control_behavior", false)? {
+        expect_object(behavior, &path)?;
+    }
+    Ok(())
+}
+
+fn check_tile(value: &Value, path: &str) -> Result<()> {
+    let object = expect_object(value, path)?;
+    check_string(object, path, "name", true)?;
+    let (position, path) = required(object, path, "position")?;
+    check_position(position, &path)
+}
+
+/// 导线为 [实体A, 接口A, 实体B, 接口B]
+fn check_wire(value: &Value, path: &str) -> Result<()> {
+    match value.as_array() {
+        Some(items) if items.len() == 4 => {
+            for (index, item) in items.iter().enumerate() {
+                if !item.is_u64() {
+                    return Err(schema_error(
+                        &format!("{}[{}]", path, index),
+                        "应为非负整数",
+                    ));
+                }
+            }
+            Ok(())
+        }
+        _ => Err(schema_error(path, "应为4个整数组成的数组")),
+    }
+}
+
+fn check_blueprint(object: &Map<String, Value>, path: &str) -> Result<()> {
+    check_array(object, path, "icons", check_icon)?;
+    check_array(object, path, "entities", check_entity)?;
+    check_array(object, path, "tiles", check_tile)?;
+    check_array(object, path, "wires", check_wire)
+}
+
+fn check_book(object: &Map<String, Value>, path: &str) -> Result<()> {
+    check_array(object, path, "icons", check_icon)?;
+    check_array(object, path, "blueprints", |entry, path| {
+        check_index(expect_object(entry, path)?, path, "index", true)?;
+        check_record(entry, path)
+    })
+}
+
+/// 检查顶层对象或蓝图书中的一项
+fn check_record(value: &Value, path: &str) -> Result<()> {
+    const KINDS: [&str; 4] = [
+        "blueprint",
+        "blueprint_book",
+        "deconstruction_planner",
+        "upgrade_planner",
+    ];
+    let object = expect_object(value, path)?;
+    let kinds: Vec<&str> = KINDS
+        .into_iter()
+        .filter(|kind| object.contains_key(*kind))
+        .collect();
+    let [kind] = kinds[..] else {
+        return Err(schema_error(
+            path,
+            "应包含且只包含 blueprint、blueprint_book、deconstruction_planner、upgrade_planner 之一",
+        ));
+    };
+    let (inner, path) = required(object, path, kind)?;
+    let inner_object = expect_object(inner, &path)?;
+    check_string(inner_object, &path, "item", true)?;
+    check_string(inner_object, &path, "label", false)?;
+    check_index(inner_object, &path, "version", false)?;
+    match kind {
+        "blueprint" => check_blueprint(inner_object, &path),
+        "blueprint_book" => check_book(inner_object, &path),
+        _ => {
+            if let Some((settings, path)) = field(inner_object, &path, "settings", false)? {
+                check_array(expect_object(settings, &path)?, &path, "icons", check_icon)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// 将反序列化出错的位置接在 path 后，得到 `$.blueprint.entities[3].always_on` 形式的路径
+fn json_path(path: &str, error_path: &serde_path_to_error::Path) -> String {
+    let mut json_path = path.to_string();
+    for segment in error_path {
+        match segment {
+            Segment::Seq { index } => json_path.push_str(&format!("[{}]", index)),
+            Segment::Map { key } => json_path.push_str(&format!(".{}", key)),
+            Segment::Enum { variant } => json_path.push_str(&format!(".{}", variant)),
+            Segment::Unknown => json_path.push_str(".?"),
+        }
+    }
+    json_path
+}
+
+/// 反序列化顶层对象或蓝图书中的一项，出错时报告具体的字段位置
+///
+/// 蓝图书中的项是展开存储的，serde 无法追踪其中的位置，因此单独解析出错的项。
+fn deserialize_record(value: &Value, path: &str) -> Result<BlueprintRecord> {
+    serde_path_to_error::deserialize(value).map_err(|error| {
+        let segments: Vec<&Segment> = error.path().iter().collect();
+        if let [Segment::Enum { variant: kind }, Segment::Map { key }, Segment::Seq { index }] =
+            segments[..]
+        {
+            if let (Some(mut entry), "blueprints") = (
+                value[kind.as_str()]["blueprints"][index]
+                    .as_object()
+                    .cloned(),
+                key.as_str(),
+            ) {
+                entry.remove("index");
+                let entry_path = format!("{}.{}.blueprints[{}]", path, kind, index);
+                if let Err(error) = deserialize_record(&Value::Object(entry), &entry_path) {
+                    return error;
+                }
+            }
+        }
+        schema_error(&json_path(path, error.path()), error.inner().to_string())
+    })
+}
+
+/// 检查已解码的蓝图字典是否符合蓝图结构
+pub fn check_blueprint_schema(dict: &Value) -> Result<BlueprintRecord> {
+    check_record(dict, "$")?;
+    // 逐项检查未覆盖的字段，如实体的开关和时刻表
+    deserialize_record(dict, "$")
+}
+
+/// 严格校验蓝图字符串，失败时给出出错的阶段和位置
+pub fn validate_blueprint_string(blueprint_string: &str) -> Result<BlueprintRecord> {
+    check_blueprint_schema(&decode_blueprint_string(blueprint_string)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::ZlibEncoder, Compression};
+    use serde_json::json;
+
+    use super::*;
+    use crate::core::blueprints_lib::dict_to_blueprint;
+
+    #[test]
+    fn test_validate_blueprint_string() {
+        let dict = json!({
+            "blueprint": {
+                "item": "blueprint",
+                "entities": [
+                    {"entity_number": 1, "name": "small-lamp", "position": {"x": 0.5, "y": 0.5}},
+                    {"entity_number": 2, "name": "small-lamp", "position": {"x": 1.5, "y": "0.5"}}
+                ]
+            }
+        });
+        let blueprint_string = dict_to_blueprint(&dict).unwrap();
+        assert_eq!(
+            validate_blueprint_string(&blueprint_string),
+            Err(ValidationError::Schema {
+                path: "$.blueprint.entities[1].position.y".to_string(),
+                message: "应为数字".to_string()
+            })
+        );
+
+        let mut dict = dict;
+        dict["blueprint"]["entities"][1]["position"]["y"] = json!(0.5);
+        let blueprint_string = dict_to_blueprint(&dict).unwrap();
+        // 粘贴时带有的空白和换行会被忽略
+        let pasted = format!(
+            "  {}\n{}\r\n",
+            &blueprint_string[..20],
+            &blueprint_string[20..]
+        );
+        assert_eq!(
+            validate_blueprint_string(&pasted).unwrap().entity_count(),
+            2
+        );
+
+        assert_eq!(
+            validate_blueprint_string(" \n"),
+            Err(ValidationError::Empty)
+        );
+        assert_eq!(
+            validate_blueprint_string(&blueprint_string[1..]),
+            Err(ValidationError::VersionPrefix {
+                offset: 0,
+                found: blueprint_string[1..].chars().next().unwrap()
+            })
+        );
+        let mut broken = blueprint_string.clone();
+        broken.replace_range(10..11, "!");
+        assert_eq!(
+            validate_blueprint_string(&broken),
+            Err(ValidationError::Base64Alphabet {
+                offset: 10,
+                found: '!'
+            })
+        );
+        assert!(matches!(
+            validate_blueprint_string(&blueprint_string[..blueprint_string.len() - 1]),
+            Err(ValidationError::Base64Padding { .. })
+        ));
+        // 截断为4的倍数后 base64 合法，但 zlib 数据不完整
+        let truncated = &blueprint_string[..1 + (blueprint_string.len() - 1) / 8 * 4];
+        assert!(matches!(
+            validate_blueprint_string(truncated),
+            Err(ValidationError::Zlib { .. })
+        ));
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(b"{\"blueprint\":\n  {\"item\" \"blueprint\"}}")
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+        let invalid_json = format!("0{}", general_purpose::STANDARD.encode(compressed));
+        assert!(matches!(
+            validate_blueprint_string(&invalid_json),
+            Err(ValidationError::Json {
+                line: 2,
+                column: 11,
+                ..
+            })
+        ));
+
+        // 逐项检查未覆盖的字段出错时报告具体位置
+        dict["blueprint"]["entities"][1]["always_on"] = json!("yes");
+        let blueprint_string = dict_to_blueprint(&dict).unwrap();
+        assert!(matches!(
+            validate_blueprint_string(&blueprint_string),
+            Err(ValidationError::Schema { path, .. })
+                if path == "$.blueprint.entities[1].always_on"
+        ));
+        dict["blueprint"]["entities"][1]["always_on"] = json!(true);
+        dict["blueprint"]["schedules"] = json!([{"locomotives": [1], "schedule": {"records": [
+            {"station": "铁矿", "wait_conditions": [{"type": "time", "ticks": -1}]}
+        ]}}]);
+        let blueprint_string = dict_to_blueprint(&dict).unwrap();
+        assert!(matches!(
+            validate_blueprint_string(&blueprint_string),
+            Err(ValidationError::Schema { path, .. })
+                if path == "$.blueprint.schedules[0].schedule.records[0].wait_conditions[0].ticks"
+        ));
+
+        // 蓝图书中的蓝图同样报告具体位置
+        let book = json!({"blueprint_book": {
+            "item": "blueprint-book",
+            "blueprints": [{"index": 0, "blueprint": dict["blueprint"].clone()}]
+        }});
+        assert!(matches!(
+            check_blueprint_schema(&book),
+            Err(ValidationError::Schema { path, .. }) if path == concat!(
+                "$.blueprint_book.blueprints[0].blueprint",
+                ".schedules[0].schedule.records[0].wait_conditions[0].ticks"
+            )
+        ));
+
+        let not_record = dict_to_blueprint(&json!({"blueprint": []})).unwrap();
+        assert_eq!(
+            validate_blueprint_string(&not_record),
+            Err(ValidationError::Schema {
+                path: "$.blueprint".to_string(),
+                message: "应为对象".to_string()
+            })
+        );
+    }
+}
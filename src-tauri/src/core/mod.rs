@@ -1,24 +1,149 @@
 use std::fmt::Display;
 
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+mod blueprints_animation;
+mod blueprints_circuit;
 mod blueprints_consts;
+mod blueprints_dict;
 mod blueprints_funcs;
+mod blueprints_image;
+mod blueprints_inspect;
 mod blueprints_lib;
+mod blueprints_render;
+mod blueprints_simulate;
+mod blueprints_transform;
+mod blueprints_validate;
+
+pub use blueprints_animation::{generate_animation_blueprint, AnimationOptions};
+pub use blueprints_dict::{
+    blueprint_signal_hash, diff_signal_dict, is_data_dump, matches_pattern, parse_signal_dict,
+    reference_warnings, signal_dict_from_blueprint, signal_dict_from_data_dump, signal_hash,
+    stamp_signal_hash, unknown_signals, DictSignal, SignalDictDiff, SignalProfile, TypeChange,
+    DEFAULT_EXCLUDED_SIGNALS,
+};
+pub use blueprints_funcs::{
+    extract_blueprint_book, generate_all_item_constant_combinator_blueprint,
+    generate_blueprint_book, generate_display_deconstruction_planner, generate_image_blueprint,
+    generate_lamp_upgrade_planner, generate_screen_blueprint, generate_tiled_display_blueprint,
+    list_blueprint_book, AllSignalsOptions, BookChild, CountMode,
+};
+pub use blueprints_image::{AlphaMode, DitherMode, ImageMode, ImageOptions, ResizeMode};
+pub use blueprints_inspect::{inspect_blueprint, BlueprintInspection, BlueprintSummary};
+pub use blueprints_lib::{
+    blueprint_to_dict, BluePrint, BlueprintRecord, BlueprintResponse, EncodeOptions, GameVersion,
+    List, SizeReport,
+};
+pub use blueprints_render::{encode_png, render_blueprint, render_blueprint_png, RenderOptions};
+pub use blueprints_simulate::{simulate_screen, SimulationIssue, SimulationReport};
+pub use blueprints_transform::{FlipAxis, Rotation, Transform};
+pub use blueprints_validate::{
+    check_blueprint_schema, decode_blueprint_string, validate_blueprint_string, ValidationError,
+};
 
-pub use blueprints_funcs::{generate_image_blueprint, generate_screen_blueprint};
+pub type Result<T, E = BluePrintError> = std::result::Result<T, E>;
 
-#[derive(Debug)]
-pub struct BluePrintError(String);
+#[derive(Debug, Clone)]
+pub enum BluePrintError {
+    /// 宽度或高度不合法
+    InvalidDimensions { width: u32, height: u32 },
+    /// 像素数量超过了信号字典能提供的信号数量
+    SignalCapacityExceeded { required: u32, capacity: u32 },
+    /// 图片无法读取或解码
+    ImageDecode(String),
+    /// 文件读写错误
+    Io(String),
+    /// 信号字典缺失或格式错误
+    SignalDictionary(String),
+    /// 蓝图字符串编码或解码错误
+    Codec(String),
+    /// 蓝图字符串校验失败，包含出错的阶段和位置
+    Validation(ValidationError),
+    /// 其它参数错误
+    InvalidInput(String),
+}
+
+impl BluePrintError {
+    /// 前端用于区分错误类型的标识
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BluePrintError::InvalidDimensions { .. } => "invalidDimensions",
+            BluePrintError::SignalCapacityExceeded { .. } => "signalCapacityExceeded",
+            BluePrintError::ImageDecode(_) => "imageDecode",
+            BluePrintError::Io(_) => "io",
+            BluePrintError::SignalDictionary(_) => "signalDictionary",
+            BluePrintError::Codec(_) => "codec",
+            BluePrintError::Validation(_) => "validation",
+            BluePrintError::InvalidInput(_) => "invalidInput",
+        }
+    }
+}
 
 impl Display for BluePrintError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", self.0)
+        match self {
+            BluePrintError::InvalidDimensions { width, height } => {
+                write!(f, "尺寸{}x{}不合法！", width, height)
+            }
+            BluePrintError::SignalCapacityExceeded { required, capacity } => {
+                write!(f, "像素总和{}超过{}上限！", required, capacity)
+            }
+            BluePrintError::ImageDecode(msg) => write!(f, "图片读取失败：{}", msg),
+            BluePrintError::Io(msg) => write!(f, "文件读写失败：{}", msg),
+            BluePrintError::SignalDictionary(msg) => write!(f, "信号字典加载失败：{}", msg),
+            BluePrintError::Codec(msg) => write!(f, "蓝图解析失败：{}", msg),
+            BluePrintError::Validation(error) => write!(f, "蓝图校验失败：{}", error),
+            BluePrintError::InvalidInput(msg) => write!(f, "{}", msg),
+        }
     }
 }
 
 impl std::error::Error for BluePrintError {}
 
+/// 序列化为 `{"kind": ..., "message": ..., ...}`，供前端展示
+impl Serialize for BluePrintError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("message", &self.to_string())?;
+        match self {
+            BluePrintError::InvalidDimensions { width, height } => {
+                map.serialize_entry("width", width)?;
+                map.serialize_entry("height", height)?;
+            }
+            BluePrintError::SignalCapacityExceeded { required, capacity } => {
+                map.serialize_entry("required", required)?;
+                map.serialize_entry("capacity", capacity)?;
+            }
+            BluePrintError::Validation(error) => {
+                map.serialize_entry("diagnostic", error)?;
+            }
+            _ => {}
+        }
+        map.end()
+    }
+}
+
 impl From<anyhow::Error> for BluePrintError {
     fn from(value: anyhow::Error) -> Self {
-        Self(value.to_string())
+        Self::Codec(value.to_string())
     }
-}
\ No newline at end of file
+}
+
+impl From<ValidationError> for BluePrintError {
+    fn from(value: ValidationError) -> Self {
+        Self::Validation(value)
+    }
+}
+
+impl From<std::io::Error> for BluePrintError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value.to_string())
+    }
+}
+
+impl From<image::ImageError> for BluePrintError {
+    fn from(value: image::ImageError) -> Self {
+        Self::ImageDecode(value.to_string())
+    }
+}
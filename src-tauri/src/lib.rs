@@ -1,18 +1,153 @@
+#[cfg(feature = "gui")]
 mod commands;
-mod core;
+pub mod core;
 
-use std::{fs::File, io::BufReader};
+#[cfg(feature = "gui")]
+use std::sync::RwLock;
+use std::{fs::File, io::BufReader, path::Path};
 
+#[cfg(feature = "gui")]
 use commands::*;
 use serde_json::Value;
-use tauri::{path::BaseDirectory, App, Manager};
+#[cfg(feature = "gui")]
+use tauri::{path::BaseDirectory, App, AppHandle, Manager};
+
+use crate::core::{
+    is_data_dump, matches_pattern, parse_signal_dict, signal_dict_from_data_dump, signal_hash,
+    BluePrintError, DictSignal, EncodeOptions, GameVersion, SignalProfile,
+    DEFAULT_EXCLUDED_SIGNALS,
+};
+
+/// 编译进程序的默认信号字典，资源文件缺失或损坏时使用
+const EMBEDDED_SIGNAL_DICT: &str = include_str!("../resources/signal_dict.json");
 
 pub struct AppData {
-    pub quality_list: [&'static str; 5],
-    pub signal_dict: Value,
-    pub signal_dict_len: usize,
+    /// 按序号排列的信号字典
+    pub signals: Vec<DictSignal>,
+    /// 当前使用的信号配置
+    pub profile: SignalProfile,
+    /// 不能作为像素信号的信号名称，可以使用 `*` 通配
+    pub excluded_signals: Vec<String>,
+    /// 当前配置下可用信号在字典中的序号
+    pub usable_signals: Vec<usize>,
+    /// 启动时加载资源文件失败的原因，此时使用的是内置字典
+    pub startup_error: Option<BluePrintError>,
+    /// 目标游戏版本的参考字典，生成的蓝图使用了其中不存在的信号时给出警告
+    pub reference_dict: Option<Vec<DictSignal>>,
+    /// 生成的蓝图记录的游戏版本，导入主版本号不同的蓝图时给出警告
+    pub game_version: GameVersion,
+    /// 生成蓝图字符串时的压缩等级和是否省略默认值
+    pub encode_options: EncodeOptions,
+}
+
+impl AppData {
+    /// 从信号字典JSON构建
+    pub fn from_signal_dict(dict: Value) -> Result<Self, BluePrintError> {
+        let mut data = Self {
+            signals: parse_signal_dict(&dict)?,
+            profile: SignalProfile::default(),
+            excluded_signals: Vec::new(),
+            usable_signals: Vec::new(),
+            startup_error: None,
+            reference_dict: None,
+            game_version: GameVersion::TARGET,
+            encode_options: EncodeOptions::default(),
+        };
+        let excluded = DEFAULT_EXCLUDED_SIGNALS
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        data.update_usable_signals(SignalProfile::default(), excluded)?;
+        Ok(data)
+    }
+
+    /// 切换信号配置，配置下没有可用信号时保持原配置
+    pub fn set_profile(&mut self, profile: SignalProfile) -> Result<(), BluePrintError> {
+        self.update_usable_signals(profile, self.excluded_signals.clone())
+    }
+
+    /// 修改排除的信号，排除后没有可用信号时保持原来的列表
+    pub fn set_excluded_signals(&mut self, excluded: Vec<String>) -> Result<(), BluePrintError> {
+        self.update_usable_signals(self.profile.clone(), excluded)
+    }
+
+    /// 信号是否被排除
+    pub fn is_excluded(&self, signal: &DictSignal) -> bool {
+        self.excluded_signals
+            .iter()
+            .any(|pattern| matches_pattern(pattern, &signal.name))
+    }
+
+    /// 按配置和排除列表重新计算可用信号
+    fn update_usable_signals(
+        &mut self,
+        profile: SignalProfile,
+        excluded: Vec<String>,
+    ) -> Result<(), BluePrintError> {
+        profile.validate()?;
+        let excluded: Vec<String> = excluded
+            .iter()
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+        let usable_signals: Vec<usize> = (0..self.signals.len())
+            .filter(|i| {
+                let signal = &self.signals[*i];
+                profile.allows(signal)
+                    && !excluded
+                        .iter()
+                        .any(|pattern| matches_pattern(pattern, &signal.name))
+            })
+            .collect();
+        if usable_signals.is_empty() {
+            return Err(BluePrintError::SignalDictionary(format!(
+                "信号字典中没有配置{}可用的信号",
+                profile.name
+            )));
+        }
+        self.profile = profile;
+        self.excluded_signals = excluded;
+        self.usable_signals = usable_signals;
+        Ok(())
+    }
+
+    /// 单个显示屏可用的像素上限，即可用信号数量 × 品质数量
+    pub fn pixel_capacity(&self) -> u32 {
+        let capacity = self.usable_signals.len() * self.profile.qualities.len();
+        capacity.min(u32::MAX as usize) as u32
+    }
+
+    /// 当前字典和配置下信号与像素对应关系的哈希
+    pub fn signal_hash(&self) -> String {
+        signal_hash(
+            self.usable_signals.iter().map(|i| &self.signals[*i]),
+            &self.profile.qualities,
+        )
+    }
+
+    /// 从文件加载信号字典，文件也可以是 `factorio --dump-data` 导出的 data-raw-dump.json
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, BluePrintError> {
+        let file = File::open(path.as_ref()).map_err(|e| {
+            BluePrintError::SignalDictionary(format!("{}：{}", path.as_ref().display(), e))
+        })?;
+        let reader = BufReader::new(file);
+        let dict: Value = serde_json::from_reader(reader)
+            .map_err(|e| BluePrintError::SignalDictionary(e.to_string()))?;
+        if is_data_dump(&dict) {
+            return Self::from_signal_dict(signal_dict_from_data_dump(&dict)?);
+        }
+        Self::from_signal_dict(dict)
+    }
+
+    /// 使用内置信号字典
+    pub fn embedded() -> Result<Self, BluePrintError> {
+        let dict: Value = serde_json::from_str(EMBEDDED_SIGNAL_DICT)
+            .map_err(|e| BluePrintError::SignalDictionary(e.to_string()))?;
+        Self::from_signal_dict(dict)
+    }
 }
 
+#[cfg(feature = "gui")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -22,28 +157,57 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .invoke_handler(tauri::generate_handler![
             generate_screen_bp,
-            generate_image_bp
+            generate_image_bp,
+            generate_tiled_display_bp,
+            generate_animation_bp,
+            generate_all_signals_bp,
+            generate_book_bp,
+            list_book_bp,
+            extract_book_bp,
+            transform_bp,
+            generate_upgrade_planner_bp,
+            generate_deconstruction_planner_bp,
+            render_bp,
+            simulate_bp,
+            inspect_blueprint,
+            get_startup_error,
+            get_signal_dict_info,
+            get_signal_profiles,
+            set_signal_profile,
+            set_excluded_signals,
+            load_signal_dict,
+            reset_signal_dict,
+            load_reference_blueprint,
+            clear_reference_dict,
+            set_game_version,
+            set_encode_options
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
-fn app_state(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
-    let signal_json_path = app
+/// 加载程序自带的信号字典，资源文件不可用时使用内置字典并记录原因
+#[cfg(feature = "gui")]
+pub(crate) fn bundled_app_data(app: &AppHandle) -> Result<AppData, BluePrintError> {
+    let loaded = app
         .path()
         .resolve("resources/signal_dict.json", BaseDirectory::Resource)
-        .unwrap();
-    let file = File::open(&signal_json_path).unwrap();
-    let reader = BufReader::new(file);
-    let dict: Value = serde_json::from_reader(reader).unwrap();
-    let mut signal_dict_len = 0;
-    if let Value::Object(m) = &dict {
-        signal_dict_len = m.len();
-    };
-    app.manage(AppData {
-        quality_list: ["normal", "uncommon", "rare", "epic", "legendary"],
-        signal_dict: dict,
-        signal_dict_len,
-    });
+        .map_err(|e| BluePrintError::SignalDictionary(e.to_string()))
+        .and_then(AppData::from_path);
+    match loaded {
+        Ok(data) => Ok(data),
+        Err(e) => {
+            let mut data = AppData::embedded()?;
+            data.startup_error = Some(e);
+            Ok(data)
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn app_state(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
+    let data = bundled_app_data(app.handle())?;
+    // 运行时可以切换信号字典
+    app.manage(RwLock::new(data));
     Ok(())
 }
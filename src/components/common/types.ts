@@ -0,0 +1,96 @@
+// 后端生成蓝图的返回结果
+export interface BlueprintResponse {
+  blueprint: string;
+  kind: string;
+  entityCount: number;
+  stringLength: number;
+  size: SizeReport;
+  warnings: string[];
+}
+
+// 蓝图预览图
+export interface RenderResponse {
+  png: string;
+  warnings: string[];
+}
+
+// 蓝图字符串各阶段的大小
+export interface SizeReport {
+  jsonBytes: number;
+  compressedBytes: number;
+  stringLength: number;
+}
+
+// 蓝图字符串的压缩等级和是否省略默认值
+export interface EncodeOptions {
+  compressionLevel: number;
+  minify: boolean;
+}
+
+// 后端返回的错误
+export interface BlueprintError {
+  kind: string;
+  message: string;
+  [key: string]: unknown;
+}
+
+// 信号配置，决定可用的品质和信号类型
+export interface SignalProfile {
+  name: string;
+  qualities: string[];
+  signalTypes: string[];
+  unavailableSignals: string[];
+}
+
+// 当前信号字典的信息
+export interface SignalDictInfo {
+  signalCount: number;
+  capacity: number;
+  profile: SignalProfile;
+  excludedSignals: string[];
+  signalHash: string;
+  gameVersion: string;
+  encodeOptions: EncodeOptions;
+}
+
+// 字典中的一个信号，物品信号没有类型
+export interface DictSignal {
+  name: string;
+  type?: string;
+}
+
+// 当前信号字典与参考字典的差异
+export interface SignalDictDiff {
+  added: DictSignal[];
+  removed: DictSignal[];
+  typeChanged: { name: string; from: string | null; to: string | null }[];
+}
+
+// 蓝图占据的范围，以格为单位
+export interface BoundingBox {
+  minX: number;
+  minY: number;
+  maxX: number;
+  maxY: number;
+}
+
+// 蓝图概要，蓝图书为其中所有蓝图的合计
+export interface BlueprintSummary {
+  kind: string;
+  label: string | null;
+  icons: { index: number; signal: { name: string; type?: string; quality?: string } }[];
+  version: string | null;
+  blueprintCount: number;
+  entityCount: number;
+  entityCounts: Record<string, number>;
+  boundingBox: BoundingBox | null;
+  wires: { red: number; green: number; copper: number };
+  filters: { combinators: number; sections: number; filters: number };
+}
+
+// inspect_blueprint 的返回值
+export interface BlueprintInspection {
+  json: string;
+  summary: BlueprintSummary;
+  warnings: string[];
+}